@@ -0,0 +1,12 @@
+CREATE TABLE IF NOT EXISTS configs (
+    filename TEXT NOT NULL PRIMARY KEY,
+    shorthand TEXT NOT NULL,
+    content TEXT NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS secrets (
+    project TEXT NOT NULL,
+    path TEXT NOT NULL,
+    content TEXT NOT NULL,
+    PRIMARY KEY (project, path)
+);
@@ -0,0 +1,2 @@
+DROP TABLE revisions;
+ALTER TABLE configs DROP COLUMN revision;
@@ -0,0 +1,11 @@
+ALTER TABLE configs ADD COLUMN revision INTEGER NOT NULL DEFAULT 1;
+
+CREATE TABLE revisions (
+    filename TEXT NOT NULL,
+    revision INTEGER NOT NULL,
+    content TEXT NOT NULL,
+    PRIMARY KEY (filename, revision)
+);
+
+INSERT INTO revisions (filename, revision, content)
+SELECT filename, revision, content FROM configs;
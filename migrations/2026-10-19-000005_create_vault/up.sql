@@ -0,0 +1,5 @@
+CREATE TABLE vault (
+    id INTEGER PRIMARY KEY NOT NULL CHECK (id = 1),
+    salt TEXT NOT NULL,
+    verifier TEXT NOT NULL
+);
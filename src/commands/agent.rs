@@ -0,0 +1,136 @@
+use {
+    crate::{error, success, vault},
+    std::{
+        io::{BufRead, Write},
+        os::unix::fs::{DirBuilderExt, PermissionsExt},
+    },
+};
+
+struct State {
+    key: Option<vault::Key>,
+    last_used: std::time::Instant,
+}
+
+/// Answers a single request, the protocol is one line in and one line out
+fn respond(state: &mut State, request: &str) -> String {
+    let (command, argument) = request.split_once(' ').unwrap_or((request, ""));
+    match command {
+        "PING" => "OK".to_string(),
+        "GET" => match state.key {
+            Some(key) => {
+                state.last_used = std::time::Instant::now();
+                format!("KEY {}", vault::encode_key(&key))
+            }
+            None => "LOCKED".to_string(),
+        },
+        "SET" => match vault::decode_key(argument) {
+            Some(key) => {
+                state.key = Some(key);
+                state.last_used = std::time::Instant::now();
+                "OK".to_string()
+            }
+            None => "ERROR Invalid key".to_string(),
+        },
+        "LOCK" => {
+            state.key = None;
+            "OK".to_string()
+        }
+        _ => "ERROR Unknown request".to_string(),
+    }
+}
+
+pub fn agent() -> seahorse::Command {
+    seahorse::Command::new("agent")
+        .description("Run the agent that keeps secrets unlocked until they are unused for a while")
+        .usage("nova agent [--timeout minutes]")
+        .flag(
+            seahorse::Flag::new("timeout", seahorse::FlagType::Int)
+                .description("Minutes of inactivity before the key is forgotten, defaults to 15")
+                .alias("t"),
+        )
+        .action(|context| {
+            let timeout = std::time::Duration::from_secs(
+                context.int_flag("timeout").unwrap_or(15).max(1) as u64 * 60,
+            );
+
+            if vault::agent_request("PING").is_some() {
+                error!("Agent is already running on", vault::AGENT_SOCKET);
+                return;
+            }
+
+            // The socket lives in a private folder so it is never reachable with wider permissions
+            let folder = std::path::Path::new(vault::AGENT_SOCKET).parent().unwrap();
+            if let Err(err) = std::fs::DirBuilder::new()
+                .recursive(true)
+                .mode(0o700)
+                .create(folder)
+                .and_then(|_| {
+                    std::fs::set_permissions(folder, std::fs::Permissions::from_mode(0o700))
+                })
+                .and_then(|_| std::os::unix::fs::chown(folder, Some(501), Some(20)))
+            {
+                error!("Unable to create agent folder", folder.display(); err);
+                return;
+            }
+
+            // A socket left behind by an agent that didn't exit cleanly would make binding fail
+            let _ = std::fs::remove_file(vault::AGENT_SOCKET);
+            let listener = match std::os::unix::net::UnixListener::bind(vault::AGENT_SOCKET) {
+                Ok(listener) => listener,
+                Err(err) => {
+                    error!("Unable to listen on", vault::AGENT_SOCKET; err);
+                    return;
+                }
+            };
+
+            if let Err(err) = std::fs::set_permissions(
+                vault::AGENT_SOCKET,
+                std::fs::Permissions::from_mode(0o600),
+            )
+            .and_then(|_| std::os::unix::fs::chown(vault::AGENT_SOCKET, Some(501), Some(20)))
+            {
+                error!("Unable to restrict agent socket", vault::AGENT_SOCKET; err);
+                return;
+            }
+
+            let state = std::sync::Arc::new(std::sync::Mutex::new(State {
+                key: None,
+                last_used: std::time::Instant::now(),
+            }));
+
+            let idle_state = state.clone();
+            std::thread::spawn(move || loop {
+                std::thread::sleep(std::time::Duration::from_secs(1));
+                let mut state = idle_state.lock().unwrap();
+                if state.key.is_some() && state.last_used.elapsed() > timeout {
+                    state.key = None;
+                }
+            });
+
+            success!("Agent listening on", vault::AGENT_SOCKET);
+
+            for stream in listener.incoming() {
+                let mut stream = match stream {
+                    Ok(stream) => stream,
+                    Err(_) => continue,
+                };
+
+                // Each client gets its own thread and a deadline, so a silent client can't block
+                // the agent from answering anyone else
+                let state = state.clone();
+                std::thread::spawn(move || {
+                    let mut request = String::new();
+                    if stream
+                        .set_read_timeout(Some(std::time::Duration::from_secs(5)))
+                        .and_then(|_| std::io::BufReader::new(&stream).read_line(&mut request))
+                        .is_err()
+                    {
+                        return;
+                    }
+
+                    let response = respond(&mut state.lock().unwrap(), request.trim());
+                    let _ = writeln!(stream, "{}", response);
+                });
+            }
+        })
+}
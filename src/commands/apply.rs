@@ -0,0 +1,156 @@
+use {
+    crate::{error, models::Secret, schema::configs, schema::secrets, success, warn},
+    diesel::prelude::*,
+};
+
+static MANIFEST: &str = "nova.toml";
+
+#[derive(serde::Deserialize)]
+struct Manifest {
+    #[serde(default)]
+    configs: Vec<String>,
+    #[serde(default)]
+    bundles: std::collections::HashMap<String, Vec<String>>,
+    #[serde(default)]
+    secrets: Vec<String>,
+    setup: Option<String>,
+}
+
+pub fn apply() -> seahorse::Command {
+    seahorse::Command::new("apply")
+        .description("Clone the configs and secrets declared in nova.toml that are missing")
+        .usage("nova apply [--yes]")
+        .flag(
+            seahorse::Flag::new("yes", seahorse::FlagType::Bool)
+                .description("Run post-clone hooks without asking for confirmation")
+                .alias("y"),
+        )
+        .action(|context| {
+            let manifest = match std::fs::read_to_string(MANIFEST) {
+                Ok(manifest) => match toml::from_str::<Manifest>(&manifest) {
+                    Ok(manifest) => manifest,
+                    Err(err) => {
+                        error!("Unable to parse manifest", MANIFEST; err);
+                        return;
+                    }
+                },
+                Err(err) => {
+                    error!("Unable to read from manifest", MANIFEST; err);
+                    return;
+                }
+            };
+
+            let mut shorthands = vec![];
+            for name in &manifest.configs {
+                for shorthand in manifest.bundles.get(name).unwrap_or(&vec![name.clone()]) {
+                    if !shorthands.contains(shorthand) {
+                        shorthands.push(shorthand.clone());
+                    }
+                }
+            }
+
+            let connection = &mut crate::connect_db();
+            let mut unsatisfied = vec![];
+            let mut missing_configs = vec![];
+
+            for shorthand in shorthands {
+                match configs::dsl::configs
+                    .filter(configs::shorthand.eq(&shorthand))
+                    .select(configs::filename)
+                    .first::<String>(connection)
+                {
+                    Ok(filename) => {
+                        if !std::path::Path::new(&filename).exists() {
+                            missing_configs.push(shorthand);
+                        }
+                    }
+                    Err(_) => {
+                        unsatisfied.push(format!("Unknown config shorthand \"{}\"", shorthand))
+                    }
+                }
+            }
+
+            if !super::configs::clone_configs(&missing_configs, None, context.bool_flag("yes")) {
+                unsatisfied.push("Some configs could not be cloned".to_string());
+            }
+
+            if !manifest.secrets.is_empty() {
+                match super::secrets::locate() {
+                    Ok(location) => {
+                        let mut missing_secrets = vec![];
+                        for path in &manifest.secrets {
+                            let path = match super::secrets::project_path(path) {
+                                Ok(path) => path,
+                                Err(err) => {
+                                    unsatisfied.push(err);
+                                    continue;
+                                }
+                            };
+
+                            if location.root.join(&path).exists() {
+                                continue;
+                            }
+
+                            match secrets::dsl::secrets
+                                .filter(secrets::project.eq(&location.project))
+                                .filter(secrets::path.eq(&path))
+                                .first::<Secret>(connection)
+                            {
+                                Ok(secret) => missing_secrets.push(secret),
+                                Err(_) => {
+                                    unsatisfied.push(format!("No secret stored for \"{}\"", path))
+                                }
+                            }
+                        }
+
+                        if !missing_secrets.is_empty() {
+                            match crate::vault::unlock(connection) {
+                                Some(key) => {
+                                    for secret in missing_secrets {
+                                        let cloned = super::secrets::clone_secret(
+                                            &location,
+                                            &secret,
+                                            &key,
+                                            &super::secrets::CloneOptions::default(),
+                                        );
+                                        if cloned != super::secrets::Cloned::Written {
+                                            unsatisfied.push(format!(
+                                                "Unable to clone secret \"{}\"",
+                                                secret.path
+                                            ));
+                                        }
+                                    }
+                                }
+                                None => unsatisfied.push("Secrets are locked".to_string()),
+                            }
+                        }
+                    }
+                    Err(err) => unsatisfied.push(format!("Unable to find the project: {}", err)),
+                }
+            }
+
+            if let Some(cli) = &manifest.setup {
+                if !["bun", "pnpm", "yarn", "npm"].contains(&cli.as_str()) {
+                    unsatisfied.push(format!("Unknown npm cli \"{}\"", cli));
+                } else if !std::fs::read_to_string("package.json")
+                    .ok()
+                    .and_then(|package| json::parse(&package).ok())
+                    .is_some_and(|package| package["scripts"]["lint"] == super::setup::SCRIPT_LINT)
+                {
+                    unsatisfied.push(format!(
+                        "package.json is not set up, run \"nova setup {} package.json\"",
+                        cli
+                    ));
+                }
+            }
+
+            if unsatisfied.is_empty() {
+                success!("Project matches", MANIFEST);
+                return;
+            }
+
+            for reason in unsatisfied {
+                warn!(reason);
+            }
+        })
+}
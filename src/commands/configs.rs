@@ -1,8 +1,467 @@
 use {
-    crate::{error, models::Config, schema::configs, success, warn},
+    crate::{
+        error,
+        formats::Format,
+        models::{Config, Revision},
+        schema::{configs, revisions},
+        success, warn,
+    },
     diesel::prelude::*,
+    sha2::Digest,
+    std::io::{Read, Write},
 };
 
+static LOCKFILE: &str = ".nova.lock";
+static MANIFEST: &str = "manifest.toml";
+static PROJECTS: &str = "/Users/mac/Projects";
+
+#[derive(serde::Serialize, serde::Deserialize)]
+struct Manifest {
+    configs: Vec<ManifestEntry>,
+}
+
+#[derive(serde::Serialize, serde::Deserialize)]
+struct ManifestEntry {
+    shorthand: String,
+    filename: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    hook: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    base: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    description: Option<String>,
+}
+
+#[derive(Default, serde::Serialize, serde::Deserialize)]
+struct Lockfile {
+    #[serde(default)]
+    configs: Vec<LockedConfig>,
+}
+
+#[derive(serde::Serialize, serde::Deserialize)]
+struct LockedConfig {
+    shorthand: String,
+    filename: String,
+    revision: i32,
+    hash: String,
+}
+
+static KNOWN_CONFIGS: [(&str, &str); 34] = [
+    ("tsconfig.json", "ts"),
+    ("jsconfig.json", "js"),
+    ("package.json", "pkg"),
+    (".gitignore", "git"),
+    (".gitattributes", "gitattributes"),
+    (".editorconfig", "ecf"),
+    (".eslintrc", "eslint"),
+    (".eslintrc.js", "eslint"),
+    (".eslintrc.cjs", "eslint"),
+    (".eslintrc.json", "eslint"),
+    (".eslintrc.yaml", "eslint"),
+    (".eslintrc.yml", "eslint"),
+    ("eslint.config.js", "eslint"),
+    ("eslint.config.mjs", "eslint"),
+    (".eslintignore", "eslintignore"),
+    (".prettierrc", "prettier"),
+    (".prettierrc.js", "prettier"),
+    (".prettierrc.json", "prettier"),
+    (".prettierrc.yaml", "prettier"),
+    (".prettierrc.yml", "prettier"),
+    ("prettier.config.js", "prettier"),
+    (".prettierignore", "prettierignore"),
+    (".npmrc", "npmrc"),
+    (".nvmrc", "nvmrc"),
+    (".husky/pre-commit", "husky"),
+    ("rustfmt.toml", "rustfmt"),
+    (".rustfmt.toml", "rustfmt"),
+    ("analysis_options.yaml", "dart"),
+    ("Dockerfile", "docker"),
+    (".dockerignore", "dockerignore"),
+    ("docker-compose.yml", "compose"),
+    ("tailwind.config.js", "tailwind"),
+    ("tailwind.config.ts", "tailwind"),
+    ("postcss.config.js", "postcss"),
+];
+
+#[derive(serde::Serialize)]
+struct Match {
+    shorthand: String,
+    filename: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    line: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    snippet: Option<String>,
+}
+
+enum Strategy {
+    Skip,
+    Overwrite,
+    Rename,
+}
+
+/// Resolves the content of a config by applying its content as a patch over its base config
+fn resolve(connection: &mut SqliteConnection, config: &Config) -> Result<String, String> {
+    resolve_chain(connection, config, &mut vec![])
+}
+
+fn resolve_chain(
+    connection: &mut SqliteConnection,
+    config: &Config,
+    visited: &mut Vec<String>,
+) -> Result<String, String> {
+    let shorthand = match &config.base {
+        Some(shorthand) => shorthand,
+        None => return Ok(config.content.clone()),
+    };
+
+    if visited.contains(&config.shorthand) {
+        return Err(format!("Circular base config \"{}\"", config.shorthand));
+    }
+    visited.push(config.shorthand.clone());
+
+    let base = configs::dsl::configs
+        .filter(configs::shorthand.eq(shorthand))
+        .first::<Config>(connection)
+        .map_err(|_| format!("Unknown base config shorthand \"{}\"", shorthand))?;
+
+    let base_format = Format::from_filename(&base.filename)
+        .ok_or(format!("Unknown file format of \"{}\"", base.filename))?;
+    let format = Format::from_filename(&config.filename)
+        .ok_or(format!("Unknown file format of \"{}\"", config.filename))?;
+
+    // Parsing drops comments, so merging configs that have them would silently lose them
+    for (config, format) in [(&base, base_format), (config, format)] {
+        if format.has_comments(&config.content) {
+            return Err(format!(
+                "Config \"{}\" has comments, which would be lost when merging configs",
+                config.shorthand
+            ));
+        }
+    }
+
+    let mut value = base_format.parse(&resolve_chain(connection, &base, visited)?)?;
+    crate::formats::merge(&mut value, format.parse(&config.content)?);
+    format.serialize(&value)
+}
+
+fn insert_config(
+    connection: &mut SqliteConnection,
+    config: &Config,
+) -> Result<usize, Box<dyn std::error::Error>> {
+    connection.transaction(|connection| {
+        let inserted = diesel::insert_into(configs::dsl::configs)
+            .values(config)
+            .execute(connection)?;
+
+        diesel::insert_into(revisions::dsl::revisions)
+            .values(&Revision {
+                filename: config.filename.clone(),
+                revision: config.revision,
+                content: resolve(connection, config)?,
+            })
+            .execute(connection)?;
+
+        Ok(inserted)
+    })
+}
+
+/// Updates the content of a config and stores the new content as its next revision
+fn update_config(
+    connection: &mut SqliteConnection,
+    filename: &str,
+    content: &str,
+) -> Result<usize, Box<dyn std::error::Error>> {
+    connection.transaction(|connection| {
+        let config = configs::dsl::configs
+            .filter(configs::filename.eq(filename))
+            .first::<Config>(connection)?;
+
+        let updated = diesel::update(configs::dsl::configs)
+            .filter(configs::filename.eq(filename))
+            .set(configs::content.eq(content))
+            .execute(connection)?;
+
+        store_revision(
+            connection,
+            Config {
+                content: content.to_string(),
+                ..config
+            },
+        )?;
+
+        Ok(updated)
+    })
+}
+
+/// Stores the resolved content of a config as its next revision,
+/// then does the same for every derived config whose resolved content changed
+fn store_revision(
+    connection: &mut SqliteConnection,
+    config: Config,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let revision = config.revision + 1;
+
+    diesel::insert_into(revisions::dsl::revisions)
+        .values(&Revision {
+            filename: config.filename.clone(),
+            revision,
+            content: resolve(connection, &config)?,
+        })
+        .execute(connection)?;
+
+    diesel::update(configs::dsl::configs)
+        .filter(configs::filename.eq(&config.filename))
+        .set(configs::revision.eq(revision))
+        .execute(connection)?;
+
+    for derived in configs::dsl::configs
+        .filter(configs::base.eq(&config.shorthand))
+        .load::<Config>(connection)?
+    {
+        let latest = revisions::dsl::revisions
+            .filter(revisions::filename.eq(&derived.filename))
+            .filter(revisions::revision.eq(derived.revision))
+            .select(revisions::content)
+            .first::<String>(connection)?;
+
+        if resolve(connection, &derived)? != latest {
+            store_revision(connection, derived)?;
+        }
+    }
+
+    Ok(())
+}
+
+fn delete_config(connection: &mut SqliteConnection, filename: &str) -> QueryResult<usize> {
+    connection.transaction(|connection| {
+        diesel::delete(revisions::dsl::revisions)
+            .filter(revisions::filename.eq(filename))
+            .execute(connection)?;
+
+        diesel::delete(configs::dsl::configs)
+            .filter(configs::filename.eq(filename))
+            .execute(connection)
+    })
+}
+
+fn hash(content: &str) -> String {
+    format!("{:x}", sha2::Sha256::digest(content))
+}
+
+fn read_lockfile(folder: &std::path::Path) -> Result<Lockfile, toml::de::Error> {
+    match std::fs::read_to_string(folder.join(LOCKFILE)) {
+        Ok(lockfile) => toml::from_str(&lockfile),
+        Err(_) => Ok(Lockfile::default()),
+    }
+}
+
+fn write_lockfile(folder: &std::path::Path, lockfile: &mut Lockfile) -> std::io::Result<()> {
+    lockfile.configs.sort_by(|a, b| a.filename.cmp(&b.filename));
+
+    let path = folder.join(LOCKFILE);
+    std::fs::write(
+        &path,
+        toml::to_string_pretty(lockfile).map_err(std::io::Error::other)?,
+    )?;
+    std::os::unix::fs::chown(&path, Some(501), Some(20))
+}
+
+/// Reports an unknown shorthand along with configs with a similar shorthand or filename
+fn unknown_shorthand(connection: &mut SqliteConnection, shorthand: &str) {
+    error!("Unknown config shorthand", shorthand);
+
+    let configs = match configs::dsl::configs.load::<Config>(connection) {
+        Ok(configs) => configs,
+        Err(_) => return,
+    };
+
+    let mut suggestions = configs
+        .iter()
+        .map(|config| {
+            let stem = std::path::Path::new(&config.filename)
+                .file_stem()
+                .map_or(String::new(), |stem| stem.to_string_lossy().to_string());
+            let similarity = [&config.shorthand, &config.filename, &stem]
+                .iter()
+                .map(|candidate| {
+                    strsim::normalized_damerau_levenshtein(
+                        &shorthand.to_lowercase(),
+                        &candidate.to_lowercase(),
+                    )
+                })
+                .fold(0.0, f64::max);
+            (similarity, config)
+        })
+        .filter(|(similarity, _)| *similarity >= 0.6)
+        .collect::<Vec<_>>();
+
+    suggestions.sort_by(|a, b| b.0.total_cmp(&a.0));
+
+    if !suggestions.is_empty() {
+        println!(
+            "Did you mean {}?",
+            suggestions
+                .iter()
+                .take(3)
+                .map(|(_, config)| format!("{} ({})", config.shorthand, config.filename))
+                .collect::<Vec<_>>()
+                .join(", ")
+        );
+    }
+}
+
+fn unique_shorthand(shorthand: &str, taken: &[&str]) -> String {
+    if !taken.contains(&shorthand) {
+        return shorthand.to_string();
+    }
+
+    (1..)
+        .map(|i| format!("{}-{}", shorthand, i))
+        .find(|s| !taken.contains(&s.as_str()))
+        .unwrap()
+}
+
+fn projects(root: &std::path::Path) -> std::io::Result<Vec<std::path::PathBuf>> {
+    let mut projects = std::fs::read_dir(root)?
+        .flatten()
+        .filter(|entry| !entry.file_name().to_string_lossy().starts_with('.'))
+        .map(|entry| entry.path())
+        .filter(|path| path.is_dir())
+        .collect::<Vec<_>>();
+
+    projects.sort();
+    Ok(projects)
+}
+
+/// Orders configs so that base configs come before the configs derived from them
+fn dependency_order(mut configs: Vec<Config>) -> Vec<Config> {
+    let mut ordered: Vec<Config> = vec![];
+
+    while !configs.is_empty() {
+        let (ready, rest): (Vec<_>, Vec<_>) = configs.into_iter().partition(|config| {
+            config
+                .base
+                .as_ref()
+                .is_none_or(|base| ordered.iter().any(|c| &c.shorthand == base))
+        });
+
+        if ready.is_empty() {
+            ordered.extend(rest);
+            break;
+        }
+
+        ordered.extend(ready);
+        configs = rest;
+    }
+
+    ordered
+}
+
+fn is_archive(path: &std::path::Path) -> bool {
+    let path = path.to_str().unwrap_or_default();
+    path.ends_with(".tar.gz") || path.ends_with(".tgz")
+}
+
+fn export_path(filename: &str) -> std::path::PathBuf {
+    std::path::PathBuf::from("configs").join(filename)
+}
+
+fn write_folder(
+    folder: &std::path::Path,
+    files: &[(std::path::PathBuf, String)],
+) -> std::io::Result<()> {
+    for (path, content) in files {
+        let absolute_path = folder.join(path);
+        std::fs::create_dir_all(absolute_path.parent().unwrap())?;
+        std::fs::write(&absolute_path, content)?;
+
+        for ancestor in path.ancestors().filter(|a| !a.as_os_str().is_empty()) {
+            std::os::unix::fs::chown(folder.join(ancestor), Some(501), Some(20))?;
+        }
+    }
+
+    std::os::unix::fs::chown(folder, Some(501), Some(20))
+}
+
+fn write_archive(
+    archive: &std::path::Path,
+    files: &[(std::path::PathBuf, String)],
+) -> std::io::Result<()> {
+    let mut builder = tar::Builder::new(flate2::write::GzEncoder::new(
+        std::fs::File::create(archive)?,
+        flate2::Compression::default(),
+    ));
+
+    for (path, content) in files {
+        let mut header = tar::Header::new_gnu();
+        header.set_size(content.len() as u64);
+        header.set_mode(0o644);
+        header.set_cksum();
+        builder.append_data(&mut header, path, content.as_bytes())?;
+    }
+
+    builder.into_inner()?.finish()?.flush()?;
+    std::os::unix::fs::chown(archive, Some(501), Some(20))
+}
+
+/// Reads the manifest of an export folder and the files it lists, ignoring anything else like the
+/// .DS_Store files that Finder leaves behind
+fn read_folder(
+    folder: &std::path::Path,
+) -> std::io::Result<std::collections::HashMap<std::path::PathBuf, String>> {
+    let mut files = std::collections::HashMap::new();
+    let manifest = match std::fs::read_to_string(folder.join(MANIFEST)) {
+        Ok(manifest) => manifest,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(files),
+        Err(err) => return Err(err),
+    };
+
+    if let Ok(Manifest { configs }) = toml::from_str(&manifest) {
+        for entry in configs {
+            let path = export_path(&entry.filename);
+            match std::fs::read_to_string(folder.join(&path)) {
+                Ok(content) => {
+                    files.insert(path, content);
+                }
+                Err(err) if err.kind() == std::io::ErrorKind::NotFound => {}
+                Err(err) => return Err(err),
+            }
+        }
+    }
+
+    files.insert(std::path::PathBuf::from(MANIFEST), manifest);
+    Ok(files)
+}
+
+fn read_archive(
+    archive: &std::path::Path,
+) -> std::io::Result<std::collections::HashMap<std::path::PathBuf, String>> {
+    let mut files = std::collections::HashMap::new();
+    let mut archive =
+        tar::Archive::new(flate2::read::GzDecoder::new(std::fs::File::open(archive)?));
+
+    for entry in archive.entries()? {
+        let mut entry = entry?;
+        if entry.header().entry_type().is_file() {
+            // Archives made with `tar -C folder .` prefix every path with ./
+            let path = entry
+                .path()?
+                .components()
+                .filter(|component| *component != std::path::Component::CurDir)
+                .collect::<std::path::PathBuf>();
+
+            // Files that aren't text, like the ._ files macOS adds, can't be configs
+            let mut content = vec![];
+            entry.read_to_end(&mut content)?;
+            if let Ok(content) = String::from_utf8(content) {
+                files.insert(path, content);
+            }
+        }
+    }
+
+    Ok(files)
+}
+
 fn list() -> seahorse::Command {
     seahorse::Command::new("list")
         .description("List all project configuration file(s) and their shorthands")
@@ -13,12 +472,20 @@ fn list() -> seahorse::Command {
                 .unwrap();
 
             let mut table = prettytable::Table::new();
-            table.set_titles(prettytable::row!["Shorthand", "Filename", "Content Length"]);
+            table.set_titles(prettytable::row![
+                "Shorthand",
+                "Filename",
+                "Base",
+                "Description",
+                "Content Length"
+            ]);
 
             for config in configs {
                 table.add_row(prettytable::row![
                     config.shorthand,
                     config.filename,
+                    config.base.unwrap_or_default(),
+                    config.description.unwrap_or_default(),
                     config.content.len()
                 ]);
             }
@@ -27,44 +494,428 @@ fn list() -> seahorse::Command {
         })
 }
 
+/// Trims a matched line to at most 80 characters around the match
+fn snippet(line: &str, start: usize) -> String {
+    let line = line.trim_end();
+    let start = line[..start.min(line.len())]
+        .chars()
+        .count()
+        .saturating_sub(20);
+    let snippet = line.chars().skip(start).take(80).collect::<String>();
+
+    format!(
+        "{}{}{}",
+        if start > 0 { "..." } else { "" },
+        if start > 0 {
+            &snippet
+        } else {
+            snippet.trim_start()
+        },
+        if line.chars().count() > start + 80 {
+            "..."
+        } else {
+            ""
+        }
+    )
+}
+
+fn grep() -> seahorse::Command {
+    seahorse::Command::new("grep")
+        .description("Search the content of all stored configuration files with a regex")
+        .usage("nova configs grep [regex] [--files-only] [--json]")
+        .flag(
+            seahorse::Flag::new("files-only", seahorse::FlagType::Bool)
+                .description("Only print the configs that match, without their lines")
+                .alias("l"),
+        )
+        .flag(
+            seahorse::Flag::new("json", seahorse::FlagType::Bool)
+                .description("Print the matches as JSON"),
+        )
+        .action(|context| {
+            let pattern = match context.args.first() {
+                Some(pattern) => pattern,
+                None => {
+                    error!("Please provide a regex to search for");
+                    return;
+                }
+            };
+
+            let regex = match regex::Regex::new(pattern) {
+                Ok(regex) => regex,
+                Err(err) => {
+                    error!("Invalid regex", pattern; err);
+                    return;
+                }
+            };
+
+            let configs = match configs::dsl::configs.load::<Config>(&mut crate::connect_db()) {
+                Ok(configs) => configs,
+                Err(err) => {
+                    error!("Unable to fetch configs"; err);
+                    return;
+                }
+            };
+
+            let files_only = context.bool_flag("files-only");
+            let mut matches = vec![];
+
+            for config in configs {
+                for (i, line) in config.content.lines().enumerate() {
+                    let found = match regex.find(line) {
+                        Some(found) => found,
+                        None => continue,
+                    };
+
+                    matches.push(Match {
+                        shorthand: config.shorthand.clone(),
+                        filename: config.filename.clone(),
+                        line: (!files_only).then_some(i + 1),
+                        snippet: (!files_only).then(|| snippet(line, found.start())),
+                    });
+
+                    if files_only {
+                        break;
+                    }
+                }
+            }
+
+            if context.bool_flag("json") {
+                println!("{}", serde_json::to_string_pretty(&matches).unwrap());
+                return;
+            }
+
+            if matches.is_empty() {
+                warn!("No stored configs match", pattern);
+                return;
+            }
+
+            for found in matches {
+                match (found.line, found.snippet) {
+                    (Some(line), Some(snippet)) => println!(
+                        "{} ({}):{}: {}",
+                        found.shorthand, found.filename, line, snippet
+                    ),
+                    _ => println!("{} ({})", found.shorthand, found.filename),
+                }
+            }
+        })
+}
+
+fn run_hook(config: &Config, yes: bool) -> bool {
+    let hook = match &config.hook {
+        Some(hook) => hook,
+        None => return true,
+    };
+
+    if !yes
+        && !crate::input::confirm(&format!(
+            "Run post-clone hook \"{}\" for \"{}\"?",
+            hook, config.shorthand
+        ))
+    {
+        warn!("Skipped post-clone hook for", config.shorthand);
+        return true;
+    }
+
+    match crate::owner_command("/bin/bash")
+        .arg("-c")
+        .arg(hook)
+        .status()
+    {
+        Ok(status) if status.success() => {
+            success!("Ran post-clone hook for", config.shorthand);
+            true
+        }
+        Ok(status) => {
+            error!(format!(
+                "Post-clone hook for \"{}\" exited with {}",
+                config.shorthand, status
+            ));
+            false
+        }
+        Err(err) => {
+            error!("Unable to run post-clone hook for", config.shorthand; err);
+            false
+        }
+    }
+}
+
+/// Clones configs into the current working directory and records them in the lockfile,
+/// returns whether every config was cloned
+pub fn clone_configs(shorthands: &[String], format: Option<Format>, yes: bool) -> bool {
+    let folder = std::path::Path::new(".");
+    let mut lockfile = match read_lockfile(folder) {
+        Ok(lockfile) => lockfile,
+        Err(err) => {
+            error!("Unable to parse lockfile", LOCKFILE; err);
+            return false;
+        }
+    };
+
+    let connection = &mut crate::connect_db();
+    let mut cloned = true;
+    for shorthand in shorthands {
+        let config = match configs::dsl::configs
+            .filter(
+                configs::shorthand
+                    .eq(shorthand)
+                    .or(configs::filename.eq(shorthand)),
+            )
+            .first::<Config>(connection)
+        {
+            Ok(config) => config,
+            Err(_) => {
+                unknown_shorthand(connection, shorthand);
+                cloned = false;
+                continue;
+            }
+        };
+
+        let content = match resolve(connection, &config) {
+            Ok(content) => content,
+            Err(err) => {
+                error!("Unable to resolve config", shorthand; err);
+                cloned = false;
+                continue;
+            }
+        };
+
+        let source = Format::from_filename(&config.filename);
+        let (filename, content) = match (format, source) {
+            (Some(format), Some(source)) if format != source => {
+                match crate::formats::convert(&content, source, format) {
+                    Ok(converted) => (
+                        std::path::Path::new(&config.filename)
+                            .with_extension(format.extension())
+                            .to_string_lossy()
+                            .to_string(),
+                        converted,
+                    ),
+                    Err(err) => {
+                        error!("Unable to convert config", shorthand; err);
+                        cloned = false;
+                        continue;
+                    }
+                }
+            }
+            (Some(_), None) => {
+                error!("Config is not a JSON, YAML or TOML file", config.filename);
+                cloned = false;
+                continue;
+            }
+            _ => (config.filename.clone(), content),
+        };
+
+        if let Err(err) = std::fs::write(std::path::PathBuf::from(&filename), &content) {
+            error!("Unable to write to file", filename; err);
+            return false;
+        }
+
+        if let Err(err) =
+            std::os::unix::fs::chown(std::path::PathBuf::from(&filename), Some(501), Some(20))
+        {
+            error!("Unable to change file owner", filename; err);
+            return false;
+        }
+
+        // Converted copies can't be diffed against the stored config, so they aren't locked
+        if filename != config.filename {
+            success!("Cloned file", filename);
+            if !run_hook(&config, yes) {
+                cloned = false;
+            }
+            continue;
+        }
+
+        lockfile.configs.retain(|c| c.filename != config.filename);
+        lockfile.configs.push(LockedConfig {
+            hash: hash(&content),
+            shorthand: config.shorthand.clone(),
+            filename: config.filename.clone(),
+            revision: config.revision,
+        });
+
+        if let Err(err) = write_lockfile(folder, &mut lockfile) {
+            error!("Unable to write to lockfile", LOCKFILE; err);
+            return false;
+        }
+
+        success!("Cloned file", config.filename);
+
+        if !run_hook(&config, yes) {
+            cloned = false;
+        }
+    }
+
+    cloned
+}
+
 fn clone() -> seahorse::Command {
     seahorse::Command::new("clone")
         .description("Clone project configuration file(s) to the current working directory")
-        .usage("nova configs clone [...shorthands]")
+        .usage("nova configs clone [...shorthands|filenames] [--as json|yaml|toml] [--yes]")
+        .flag(
+            seahorse::Flag::new("yes", seahorse::FlagType::Bool)
+                .description("Run post-clone hooks without asking for confirmation")
+                .alias("y"),
+        )
+        .flag(
+            seahorse::Flag::new("as", seahorse::FlagType::String)
+                .description("Convert structured configs to another format while cloning"),
+        )
         .action(|context| {
-            for shorthand in &context.args {
-                let config = match configs::dsl::configs
-                    .filter(configs::shorthand.eq(shorthand))
-                    .first::<Config>(&mut crate::connect_db())
-                {
-                    Ok(config) => config,
-                    Err(_) => {
-                        error!("Unknown config shorthand", shorthand);
-                        continue;
+            let format = match context.string_flag("as") {
+                Ok(format) => match Format::from_extension(&format) {
+                    Some(format) => Some(format),
+                    None => {
+                        error!("Unknown format, expected json, yaml or toml", format);
+                        return;
                     }
-                };
+                },
+                Err(_) => None,
+            };
 
-                if let Err(err) =
-                    std::fs::write(std::path::PathBuf::from(&config.filename), config.content)
-                {
-                    error!("Unable to write to file", config.filename; err);
+            if !context.args.is_empty() {
+                clone_configs(&context.args, format, context.bool_flag("yes"));
+                return;
+            }
+
+            if !crate::input::is_interactive() {
+                error!("Please provide some shorthands to clone");
+                return;
+            }
+
+            let configs = match configs::dsl::configs.load::<Config>(&mut crate::connect_db()) {
+                Ok(configs) => configs,
+                Err(err) => {
+                    error!("Unable to fetch configs"; err);
                     return;
                 }
+            };
+
+            let shorthand_width = configs.iter().map(|c| c.shorthand.len()).max().unwrap_or(0);
+            let filename_width = configs.iter().map(|c| c.filename.len()).max().unwrap_or(0);
+            let items = configs
+                .iter()
+                .map(|config| {
+                    format!(
+                        "{:shorthand_width$}  {:filename_width$}  {}{}",
+                        config.shorthand,
+                        config.filename,
+                        if std::path::Path::new(&config.filename).exists() {
+                            "(exists) "
+                        } else {
+                            ""
+                        },
+                        config.description.as_deref().unwrap_or_default(),
+                    )
+                })
+                .collect::<Vec<_>>();
+
+            match crate::input::pick("Configs to clone", &items, &vec![false; items.len()]) {
+                Some(indexes) if !indexes.is_empty() => {
+                    let shorthands = indexes
+                        .into_iter()
+                        .map(|i| configs[i].shorthand.clone())
+                        .collect::<Vec<_>>();
+                    clone_configs(&shorthands, format, context.bool_flag("yes"));
+                }
+                _ => {
+                    warn!("No configs selected");
+                }
+            }
+        })
+}
 
-                if let Err(err) = std::os::unix::fs::chown(
-                    std::path::PathBuf::from(&config.filename),
-                    Some(501),
-                    Some(20),
-                ) {
-                    error!("Unable to change file owner", config.filename; err);
+/// Highlights content for the terminal, based on the syntax of the filename
+fn highlight(filename: &str, content: &str) -> String {
+    let syntaxes = syntect::parsing::SyntaxSet::load_defaults_newlines();
+    let themes = syntect::highlighting::ThemeSet::load_defaults();
+    let syntax = syntaxes
+        .find_syntax_for_file(filename)
+        .ok()
+        .flatten()
+        .or_else(|| syntaxes.find_syntax_by_first_line(content))
+        .unwrap_or_else(|| syntaxes.find_syntax_plain_text());
+
+    let mut highlighter =
+        syntect::easy::HighlightLines::new(syntax, &themes.themes["base16-ocean.dark"]);
+    let mut output = String::new();
+    for line in syntect::util::LinesWithEndings::from(content) {
+        match highlighter.highlight_line(line, &syntaxes) {
+            Ok(ranges) => output += &syntect::util::as_24_bit_terminal_escaped(&ranges, false),
+            Err(_) => output += line,
+        }
+    }
+
+    output + "\x1b[0m"
+}
+
+fn show() -> seahorse::Command {
+    seahorse::Command::new("show")
+        .description("Print a project configuration file as it would be cloned")
+        .usage("nova configs show [shorthand] [--revision number]")
+        .flag(
+            seahorse::Flag::new("revision", seahorse::FlagType::Int)
+                .description("Print an older revision of the configuration file")
+                .alias("r"),
+        )
+        .action(|context| {
+            let shorthand = match context.args.first() {
+                Some(shorthand) => shorthand,
+                None => {
+                    error!("Please provide a shorthand to show");
                     return;
                 }
+            };
 
-                success!("Cloned file", config.filename);
-            }
+            let connection = &mut crate::connect_db();
+            let config = match configs::dsl::configs
+                .filter(configs::shorthand.eq(shorthand))
+                .first::<Config>(connection)
+            {
+                Ok(config) => config,
+                Err(_) => {
+                    unknown_shorthand(connection, shorthand);
+                    return;
+                }
+            };
 
-            if context.args.is_empty() {
-                error!("Please provide some shorthands to clone");
+            let content = match context.int_flag("revision") {
+                Ok(revision) => match revisions::dsl::revisions
+                    .filter(revisions::filename.eq(&config.filename))
+                    .filter(revisions::revision.eq(revision as i32))
+                    .select(revisions::content)
+                    .first::<String>(connection)
+                {
+                    Ok(content) => content,
+                    Err(_) => {
+                        error!(
+                            format!("No revision {} stored for config", revision),
+                            shorthand
+                        );
+                        println!("Latest revision is {}", config.revision);
+                        return;
+                    }
+                },
+                Err(_) => match resolve(connection, &config) {
+                    Ok(content) => content,
+                    Err(err) => {
+                        error!("Unable to resolve config", shorthand; err);
+                        return;
+                    }
+                },
+            };
+
+            let output = if std::io::IsTerminal::is_terminal(&std::io::stdout()) {
+                highlight(&config.filename, &content)
+            } else {
+                content
+            };
+
+            if let Err(err) = std::io::stdout().write_all(output.as_bytes()) {
+                error!("Unable to print config", shorthand; err);
             }
         })
 }
@@ -82,13 +933,14 @@ fn vim() -> seahorse::Command {
                 }
             };
 
+            let connection = &mut crate::connect_db();
             let config = match configs::dsl::configs
                 .filter(configs::shorthand.eq(shorthand))
-                .first::<Config>(&mut crate::connect_db())
+                .first::<Config>(connection)
             {
                 Ok(config) => config,
                 Err(_) => {
-                    error!("Unknown config shorthand", shorthand);
+                    unknown_shorthand(connection, shorthand);
                     return;
                 }
             };
@@ -136,11 +988,7 @@ fn vim() -> seahorse::Command {
                 return;
             }
 
-            match diesel::update(configs::dsl::configs)
-                .filter(configs::shorthand.eq(&shorthand))
-                .set(configs::content.eq(&content))
-                .execute(&mut crate::connect_db())
-            {
+            match update_config(connection, &config.filename, &content) {
                 Ok(_) => {
                     success!("Updated config", &config.filename);
                 }
@@ -153,8 +1001,20 @@ fn vim() -> seahorse::Command {
 
 fn add() -> seahorse::Command {
     seahorse::Command::new("add")
-        .description("Add a new configuration file, uses file content if the file exists")
-        .usage("nova configs add [shorthand] [filename]")
+        .description("Add a new configuration file, reads the content from stdin if the path is -")
+        .usage("nova configs add [shorthand] [filename] [path/to/file|-] [--base shorthand]")
+        .flag(
+            seahorse::Flag::new("base", seahorse::FlagType::String)
+                .description("Store the file as a patch over the content of another config"),
+        )
+        .flag(
+            seahorse::Flag::new("description", seahorse::FlagType::String)
+                .description("Describe what the configuration file is for"),
+        )
+        .flag(
+            seahorse::Flag::new("allow-empty", seahorse::FlagType::Bool)
+                .description("Store the config even if there is no content"),
+        )
         .action(|context| {
             let shorthand = match context.args.first() {
                 Some(shorthand) => shorthand,
@@ -172,18 +1032,32 @@ fn add() -> seahorse::Command {
                 }
             };
 
-            let content = match std::fs::read_to_string(std::path::PathBuf::from(&filename)) {
+            // Escalating to sudo restarts nova, so connect before reading from stdin
+            let connection = &mut crate::connect_db();
+
+            let path = context.args.get(2).unwrap_or(filename);
+            let content = match crate::input::read_content(path) {
                 Ok(content) => content,
-                Err(_) => {
-                    warn!("Could not read file data", filename);
+                Err(err) if context.bool_flag("allow-empty") => {
+                    warn!("Could not read file data", path);
+                    println!("{}", err);
                     String::new()
                 }
+                Err(err) => {
+                    error!("Unable to read from file", path; err);
+                    return;
+                }
             };
 
+            if content.is_empty() && !context.bool_flag("allow-empty") {
+                error!("Refusing to store an empty config, use --allow-empty to store it anyway");
+                return;
+            }
+
             match configs::dsl::configs
                 .filter(configs::shorthand.eq(shorthand))
                 .count()
-                .get_result::<i64>(&mut crate::connect_db())
+                .get_result::<i64>(connection)
             {
                 Ok(configs) => {
                     if configs != 0 {
@@ -200,7 +1074,7 @@ fn add() -> seahorse::Command {
             match configs::dsl::configs
                 .filter(configs::filename.eq(filename))
                 .count()
-                .get_result::<i64>(&mut crate::connect_db())
+                .get_result::<i64>(connection)
             {
                 Ok(configs) => {
                     if configs != 0 {
@@ -218,12 +1092,13 @@ fn add() -> seahorse::Command {
                 filename: filename.to_string(),
                 shorthand: shorthand.to_string(),
                 content,
+                revision: 1,
+                hook: None,
+                base: context.string_flag("base").ok(),
+                description: context.string_flag("description").ok(),
             };
 
-            match diesel::insert_into(configs::dsl::configs)
-                .values(&config)
-                .execute(&mut crate::connect_db())
-            {
+            match insert_config(connection, &config) {
                 Ok(_) => {
                     success!(format!(
                         "Added config \"{shorthand}\" which expands to \"{filename}\""
@@ -249,16 +1124,38 @@ fn remove() -> seahorse::Command {
                 }
             };
 
-            match diesel::delete(configs::dsl::configs)
+            let connection = &mut crate::connect_db();
+            let filename = match configs::dsl::configs
                 .filter(configs::shorthand.eq(&shorthand))
-                .execute(&mut crate::connect_db())
+                .select(configs::filename)
+                .first::<String>(connection)
             {
-                Ok(deleted) => {
-                    if deleted == 0 {
-                        error!("Unknown config shorthand", shorthand);
-                    } else {
-                        success!("Removed config", shorthand);
-                    }
+                Ok(filename) => filename,
+                Err(_) => {
+                    unknown_shorthand(connection, shorthand);
+                    return;
+                }
+            };
+
+            match configs::dsl::configs
+                .filter(configs::base.eq(&shorthand))
+                .select(configs::shorthand)
+                .first::<String>(connection)
+            {
+                Ok(derived) => {
+                    error!("Config is the base of another config", derived);
+                    return;
+                }
+                Err(diesel::result::Error::NotFound) => {}
+                Err(err) => {
+                    error!("Unable to fetch configs"; err);
+                    return;
+                }
+            }
+
+            match delete_config(connection, &filename) {
+                Ok(_) => {
+                    success!("Removed config", shorthand);
                 }
                 Err(err) => {
                     error!("Unable to delete config", shorthand; err);
@@ -267,13 +1164,995 @@ fn remove() -> seahorse::Command {
         })
 }
 
+fn export() -> seahorse::Command {
+    seahorse::Command::new("export")
+        .description("Export all configuration files to a folder or a .tar.gz archive")
+        .usage("nova configs export [path/to/folder|path/to/archive.tar.gz]")
+        .action(|context| {
+            let path = match context.args.first() {
+                Some(path) => std::path::PathBuf::from(path),
+                None => {
+                    error!("Please provide a folder or archive to export to");
+                    return;
+                }
+            };
+
+            let configs = match configs::dsl::configs.load::<Config>(&mut crate::connect_db()) {
+                Ok(configs) => dependency_order(configs),
+                Err(err) => {
+                    error!("Unable to fetch configs"; err);
+                    return;
+                }
+            };
+
+            let manifest = match toml::to_string_pretty(&Manifest {
+                configs: configs
+                    .iter()
+                    .map(|config| ManifestEntry {
+                        shorthand: config.shorthand.clone(),
+                        filename: config.filename.clone(),
+                        hook: config.hook.clone(),
+                        base: config.base.clone(),
+                        description: config.description.clone(),
+                    })
+                    .collect(),
+            }) {
+                Ok(manifest) => manifest,
+                Err(err) => {
+                    error!("Unable to create manifest"; err);
+                    return;
+                }
+            };
+
+            let mut files = vec![(std::path::PathBuf::from(MANIFEST), manifest)];
+            for config in configs {
+                files.push((export_path(&config.filename), config.content));
+            }
+
+            let result = if is_archive(&path) {
+                write_archive(&path, &files)
+            } else {
+                write_folder(&path, &files)
+            };
+
+            match result {
+                Ok(_) => {
+                    success!(format!(
+                        "Exported {} config(s) to \"{}\"",
+                        files.len() - 1,
+                        path.display()
+                    ));
+                }
+                Err(err) => {
+                    error!("Unable to export configs", path.display(); err);
+                }
+            }
+        })
+}
+
+fn import() -> seahorse::Command {
+    seahorse::Command::new("import")
+        .description("Import configuration files from an export made with `nova configs export`")
+        .usage("nova configs import [path/to/export] [--strategy skip|overwrite|rename]")
+        .flag(
+            seahorse::Flag::new("strategy", seahorse::FlagType::String).description(
+                "How to handle configs that already exist: skip (default), overwrite or rename",
+            ),
+        )
+        .action(|context| {
+            let path = match context.args.first() {
+                Some(path) => std::path::PathBuf::from(path),
+                None => {
+                    error!("Please provide a folder or archive to import from");
+                    return;
+                }
+            };
+
+            let strategy = match context.string_flag("strategy") {
+                Ok(strategy) => match strategy.as_ref() {
+                    "skip" => Strategy::Skip,
+                    "overwrite" => Strategy::Overwrite,
+                    "rename" => Strategy::Rename,
+                    _ => {
+                        error!("Unknown conflict strategy", strategy);
+                        return;
+                    }
+                },
+                Err(_) => Strategy::Skip,
+            };
+
+            let files = if is_archive(&path) {
+                read_archive(&path)
+            } else {
+                read_folder(&path)
+            };
+
+            let files = match files {
+                Ok(files) => files,
+                Err(err) => {
+                    error!("Unable to read export", path.display(); err);
+                    return;
+                }
+            };
+
+            let manifest = match files.get(std::path::Path::new(MANIFEST)) {
+                Some(manifest) => match toml::from_str::<Manifest>(manifest) {
+                    Ok(manifest) => manifest,
+                    Err(err) => {
+                        error!("Unable to parse manifest"; err);
+                        return;
+                    }
+                },
+                None => {
+                    error!("No manifest found in export", path.display());
+                    return;
+                }
+            };
+
+            let connection = &mut crate::connect_db();
+            let existing = match configs::dsl::configs.load::<Config>(connection) {
+                Ok(configs) => configs,
+                Err(err) => {
+                    error!("Unable to fetch configs"; err);
+                    return;
+                }
+            };
+
+            // Exports list bases first, so configs based on a renamed config can follow the rename
+            let mut renamed = std::collections::HashMap::new();
+            for entry in manifest.configs {
+                let content = match files.get(&export_path(&entry.filename)) {
+                    Some(content) => content.clone(),
+                    None => {
+                        error!("Missing file in export", entry.filename);
+                        continue;
+                    }
+                };
+
+                let conflicts = existing
+                    .iter()
+                    .filter(|c| c.shorthand == entry.shorthand || c.filename == entry.filename)
+                    .collect::<Vec<_>>();
+
+                if conflicts.iter().any(|c| {
+                    c.shorthand == entry.shorthand
+                        && c.filename == entry.filename
+                        && c.content == content
+                }) {
+                    warn!("Config already up to date", entry.shorthand);
+                    continue;
+                }
+
+                let mut config = Config {
+                    filename: entry.filename,
+                    shorthand: entry.shorthand,
+                    content,
+                    revision: 1,
+                    hook: entry.hook,
+                    base: entry
+                        .base
+                        .map(|base| renamed.get(&base).cloned().unwrap_or(base)),
+                    description: entry.description,
+                };
+
+                if !conflicts.is_empty() {
+                    match strategy {
+                        Strategy::Skip => {
+                            warn!("Skipped existing config", config.shorthand);
+                            continue;
+                        }
+                        Strategy::Overwrite => {}
+                        Strategy::Rename => {
+                            if conflicts.iter().any(|c| c.filename == config.filename) {
+                                warn!(
+                                    "Unable to rename config with an existing filename",
+                                    config.filename
+                                );
+                                continue;
+                            }
+
+                            let shorthand = unique_shorthand(
+                                &config.shorthand,
+                                &existing
+                                    .iter()
+                                    .map(|c| c.shorthand.as_str())
+                                    .collect::<Vec<_>>(),
+                            );
+                            renamed.insert(config.shorthand.clone(), shorthand.clone());
+                            config.shorthand = shorthand;
+                        }
+                    }
+                }
+
+                let result =
+                    connection.transaction::<_, Box<dyn std::error::Error>, _>(|connection| {
+                        if let Strategy::Overwrite = strategy {
+                            for conflict in
+                                conflicts.iter().filter(|c| c.filename != config.filename)
+                            {
+                                delete_config(connection, &conflict.filename)?;
+                            }
+
+                            if conflicts.iter().any(|c| c.filename == config.filename) {
+                                diesel::update(configs::dsl::configs)
+                                    .filter(configs::filename.eq(&config.filename))
+                                    .set((
+                                        configs::shorthand.eq(&config.shorthand),
+                                        configs::hook.eq(&config.hook),
+                                        configs::base.eq(&config.base),
+                                        configs::description.eq(&config.description),
+                                    ))
+                                    .execute(connection)?;
+                                return update_config(
+                                    connection,
+                                    &config.filename,
+                                    &config.content,
+                                );
+                            }
+                        }
+
+                        insert_config(connection, &config)
+                    });
+
+                match result {
+                    Ok(_) => {
+                        success!(format!(
+                            "Imported config \"{}\" which expands to \"{}\"",
+                            config.shorthand, config.filename
+                        ));
+                    }
+                    Err(err) => {
+                        error!("Unable to import config", config.shorthand; err);
+                    }
+                }
+            }
+        })
+}
+
+fn harvest() -> seahorse::Command {
+    seahorse::Command::new("harvest")
+        .description("Scan a project for known configuration files, then add or update them")
+        .usage("nova configs harvest [path/to/project]")
+        .action(|context| {
+            let folder = std::path::PathBuf::from(context.args.first().map_or(".", |a| a.as_str()));
+            if !folder.is_dir() {
+                error!("Unable to find project folder", folder.display());
+                return;
+            }
+
+            let mut known_configs = KNOWN_CONFIGS
+                .iter()
+                .map(|(filename, shorthand)| (filename.to_string(), shorthand.to_string()))
+                .collect::<Vec<_>>();
+
+            if let Ok(entries) = std::fs::read_dir(folder.join(".github/workflows")) {
+                for entry in entries.flatten() {
+                    let name = entry.file_name().to_string_lossy().to_string();
+                    if let Some(stem) = name
+                        .strip_suffix(".yml")
+                        .or_else(|| name.strip_suffix(".yaml"))
+                    {
+                        known_configs.push((
+                            format!(".github/workflows/{}", name),
+                            format!("gh-{}", stem),
+                        ));
+                    }
+                }
+            }
+
+            let connection = &mut crate::connect_db();
+            let existing = match configs::dsl::configs.load::<Config>(connection) {
+                Ok(configs) => configs,
+                Err(err) => {
+                    error!("Unable to fetch configs"; err);
+                    return;
+                }
+            };
+
+            let mut taken = existing
+                .iter()
+                .map(|c| c.shorthand.clone())
+                .collect::<Vec<_>>();
+            let mut candidates = vec![];
+
+            for (filename, shorthand) in known_configs {
+                let content = match std::fs::read_to_string(folder.join(&filename)) {
+                    Ok(content) => content,
+                    Err(_) => continue,
+                };
+
+                match existing.iter().find(|c| c.filename == filename) {
+                    Some(config) if config.content == content => {
+                        println!("Identical config \"{}\"", config.shorthand);
+                    }
+                    Some(config) if config.base.is_some() => {
+                        if resolve(connection, config).is_ok_and(|resolved| resolved == content) {
+                            println!("Identical config \"{}\"", config.shorthand);
+                        } else {
+                            warn!("Skipped modified config with a base", config.shorthand);
+                        }
+                    }
+                    Some(config) => {
+                        candidates.push((
+                            config.shorthand.clone(),
+                            filename,
+                            content,
+                            Some(config),
+                        ));
+                    }
+                    None => {
+                        let shorthand = unique_shorthand(
+                            &shorthand,
+                            &taken.iter().map(|s| s.as_str()).collect::<Vec<_>>(),
+                        );
+                        taken.push(shorthand.clone());
+                        candidates.push((shorthand, filename, content, None));
+                    }
+                }
+            }
+
+            if candidates.is_empty() {
+                warn!("No new or modified configs found in", folder.display());
+                return;
+            }
+
+            let mut table = prettytable::Table::new();
+            table.set_titles(prettytable::row!["#", "Shorthand", "Filename", "Status"]);
+
+            for (i, (shorthand, filename, _, config)) in candidates.iter().enumerate() {
+                table.add_row(prettytable::row![
+                    i + 1,
+                    shorthand,
+                    filename,
+                    if config.is_some() { "Modified" } else { "New" }
+                ]);
+            }
+
+            table.printstd();
+
+            let selection =
+                crate::input::prompt("Configs to harvest (e.g. 1,3-4, empty for all): ");
+            let indexes = match crate::input::select(&selection, candidates.len()) {
+                Some(indexes) => indexes,
+                None => {
+                    error!("Invalid selection", selection);
+                    return;
+                }
+            };
+
+            for i in indexes {
+                let (shorthand, filename, content, config) = &candidates[i];
+
+                if let Some(config) = config {
+                    println!("{}", diffy::create_patch(&config.content, content));
+                    if !crate::input::confirm(&format!("Update config \"{}\"?", shorthand)) {
+                        warn!("Skipped config", shorthand);
+                        continue;
+                    }
+
+                    match update_config(connection, filename, content) {
+                        Ok(_) => {
+                            success!("Updated config", filename);
+                        }
+                        Err(err) => {
+                            error!("Unable to update config", filename; err);
+                        }
+                    }
+
+                    continue;
+                }
+
+                let input = crate::input::prompt(&format!(
+                    "Shorthand for \"{}\" [{}]: ",
+                    filename, shorthand
+                ));
+                let shorthand = if input.is_empty() {
+                    shorthand.to_string()
+                } else if taken.contains(&input) {
+                    error!("Shorthand already exists", input);
+                    continue;
+                } else {
+                    taken.push(input.clone());
+                    input
+                };
+
+                match insert_config(
+                    connection,
+                    &Config {
+                        filename: filename.to_string(),
+                        shorthand: shorthand.clone(),
+                        content: content.to_string(),
+                        revision: 1,
+                        hook: None,
+                        base: None,
+                        description: None,
+                    },
+                ) {
+                    Ok(_) => {
+                        success!(format!(
+                            "Added config \"{shorthand}\" which expands to \"{filename}\""
+                        ));
+                    }
+                    Err(err) => {
+                        error!("Unable to store new config", shorthand; err);
+                    }
+                }
+            }
+        })
+}
+
+fn audit() -> seahorse::Command {
+    seahorse::Command::new("audit")
+        .description("Compare the configuration files of every project against the stored configs")
+        .usage("nova configs audit [path/to/projects]")
+        .action(|context| {
+            let root =
+                std::path::PathBuf::from(context.args.first().map_or(PROJECTS, |a| a.as_str()));
+
+            let projects = match projects(&root) {
+                Ok(projects) => projects,
+                Err(err) => {
+                    error!("Unable to read projects folder", root.display(); err);
+                    return;
+                }
+            };
+
+            let connection = &mut crate::connect_db();
+            let mut configs = match configs::dsl::configs.load::<Config>(connection) {
+                Ok(configs) => configs,
+                Err(err) => {
+                    error!("Unable to fetch configs"; err);
+                    return;
+                }
+            };
+
+            for config in configs.iter_mut() {
+                match resolve(connection, config) {
+                    Ok(content) => config.content = content,
+                    Err(err) => {
+                        error!("Unable to resolve config", config.shorthand; err);
+                        return;
+                    }
+                }
+            }
+
+            let matrix = projects
+                .iter()
+                .map(|project| {
+                    configs
+                        .iter()
+                        .map(|config| {
+                            match std::fs::read_to_string(project.join(&config.filename)) {
+                                Ok(content) if content == config.content => "identical",
+                                Ok(_) => "modified",
+                                Err(_) => "missing",
+                            }
+                        })
+                        .collect::<Vec<_>>()
+                })
+                .collect::<Vec<_>>();
+
+            let columns = (0..configs.len())
+                .filter(|&i| matrix.iter().any(|row| row[i] != "missing"))
+                .collect::<Vec<_>>();
+
+            if columns.is_empty() {
+                warn!(
+                    "No stored configs found in any project under",
+                    root.display()
+                );
+                return;
+            }
+
+            let mut table = prettytable::Table::new();
+            table.set_titles(prettytable::Row::new(
+                std::iter::once("Project")
+                    .chain(columns.iter().map(|&i| configs[i].shorthand.as_str()))
+                    .map(prettytable::Cell::new)
+                    .collect(),
+            ));
+
+            for (project, row) in projects.iter().zip(&matrix) {
+                table.add_row(prettytable::Row::new(
+                    std::iter::once(project.file_name().unwrap().to_string_lossy().as_ref())
+                        .chain(columns.iter().map(|&i| row[i]))
+                        .map(prettytable::Cell::new)
+                        .collect(),
+                ));
+            }
+
+            table.printstd();
+        })
+}
+
+fn propagate() -> seahorse::Command {
+    seahorse::Command::new("propagate")
+        .description("Write a stored configuration file into every project that uses it")
+        .usage(concat!(
+            "nova configs propagate [shorthand] [--projects glob] [--root folder] [--add] ",
+            "[--force] [--commit]"
+        ))
+        .flag(
+            seahorse::Flag::new("projects", seahorse::FlagType::String)
+                .description("Only propagate to projects whose folder name matches this glob"),
+        )
+        .flag(
+            seahorse::Flag::new("root", seahorse::FlagType::String)
+                .description("Folder containing all projects, defaults to /Users/mac/Projects"),
+        )
+        .flag(
+            seahorse::Flag::new("add", seahorse::FlagType::Bool)
+                .description("Also write the file into projects that don't have it yet"),
+        )
+        .flag(
+            seahorse::Flag::new("force", seahorse::FlagType::Bool)
+                .description("Write into git repositories with uncommitted changes"),
+        )
+        .flag(
+            seahorse::Flag::new("commit", seahorse::FlagType::Bool)
+                .description("Create a git commit with the updated file in each project"),
+        )
+        .action(|context| {
+            let shorthand = match context.args.first() {
+                Some(shorthand) => shorthand,
+                None => {
+                    error!("Please provide a shorthand");
+                    return;
+                }
+            };
+
+            let pattern = match context.string_flag("projects") {
+                Ok(pattern) => match glob::Pattern::new(&pattern) {
+                    Ok(pattern) => Some(pattern),
+                    Err(err) => {
+                        error!("Invalid projects glob", pattern; err);
+                        return;
+                    }
+                },
+                Err(_) => None,
+            };
+
+            let root = std::path::PathBuf::from(
+                context
+                    .string_flag("root")
+                    .unwrap_or_else(|_| PROJECTS.to_string()),
+            );
+
+            let connection = &mut crate::connect_db();
+            let mut config = match configs::dsl::configs
+                .filter(configs::shorthand.eq(shorthand))
+                .first::<Config>(connection)
+            {
+                Ok(config) => config,
+                Err(_) => {
+                    error!("Unknown config shorthand", shorthand);
+                    return;
+                }
+            };
+
+            config.content = match resolve(connection, &config) {
+                Ok(content) => content,
+                Err(err) => {
+                    error!("Unable to resolve config", shorthand; err);
+                    return;
+                }
+            };
+
+            let projects = match projects(&root) {
+                Ok(projects) => projects,
+                Err(err) => {
+                    error!("Unable to read projects folder", root.display(); err);
+                    return;
+                }
+            };
+
+            let (mut updated, mut identical, mut skipped, mut failed) = (0, 0, 0, 0);
+
+            for project in projects {
+                let name = project.file_name().unwrap().to_string_lossy().to_string();
+                if pattern
+                    .as_ref()
+                    .is_some_and(|pattern| !pattern.matches(&name))
+                {
+                    continue;
+                }
+
+                let path = project.join(&config.filename);
+                match std::fs::read_to_string(&path) {
+                    Ok(content) if content == config.content => {
+                        identical += 1;
+                        continue;
+                    }
+                    Ok(_) => {}
+                    Err(_) if !context.bool_flag("add") => continue,
+                    Err(_) => {}
+                }
+
+                let status = crate::git_as_user(&project, &["status", "--porcelain"]);
+                if status.is_none() && project.join(".git").exists() {
+                    warn!("Skipped project with unreadable git status", name);
+                    skipped += 1;
+                    continue;
+                }
+
+                if status.as_ref().is_some_and(|s| !s.is_empty()) && !context.bool_flag("force") {
+                    warn!("Skipped project with uncommitted changes", name);
+                    skipped += 1;
+                    continue;
+                }
+
+                if let Err(err) = std::fs::create_dir_all(path.parent().unwrap())
+                    .and_then(|_| std::fs::write(&path, &config.content))
+                {
+                    error!("Unable to write to file", path.display(); err);
+                    failed += 1;
+                    continue;
+                }
+
+                if let Err(err) = std::os::unix::fs::chown(&path, Some(501), Some(20)) {
+                    error!("Unable to change file owner", path.display(); err);
+                    failed += 1;
+                    continue;
+                }
+
+                let mut paths = vec![config.filename.as_str()];
+                if let Ok(mut lockfile) = read_lockfile(&project) {
+                    if let Some(locked) = lockfile
+                        .configs
+                        .iter_mut()
+                        .find(|c| c.filename == config.filename)
+                    {
+                        locked.revision = config.revision;
+                        locked.hash = hash(&config.content);
+
+                        if let Err(err) = write_lockfile(&project, &mut lockfile) {
+                            error!("Unable to write to lockfile", name; err);
+                            failed += 1;
+                            continue;
+                        }
+
+                        let tracked = ["ls-files", "--error-unmatch", LOCKFILE];
+                        if crate::git_as_user(&project, &tracked).is_some() {
+                            paths.push(LOCKFILE);
+                        }
+                    }
+                }
+
+                if context.bool_flag("commit") {
+                    let message = format!("Update {}", config.filename);
+                    let add = [&["add", "--"], paths.as_slice()].concat();
+                    let commit = [&["commit", "-m", &message, "--"], paths.as_slice()].concat();
+                    if status.is_none()
+                        || crate::git_as_user(&project, &add).is_none()
+                        || crate::git_as_user(&project, &commit).is_none()
+                    {
+                        error!("Unable to commit file in project", name);
+                        failed += 1;
+                        continue;
+                    }
+                }
+
+                success!("Propagated config to project", name);
+                updated += 1;
+            }
+
+            println!(
+                "Updated {}, identical {}, skipped {}, failed {}",
+                updated, identical, skipped, failed
+            );
+        })
+}
+
+fn outdated() -> seahorse::Command {
+    seahorse::Command::new("outdated")
+        .description(
+            "List cloned configuration files whose stored version has changed since cloning",
+        )
+        .usage("nova configs outdated")
+        .action(|_| {
+            let lockfile = match read_lockfile(std::path::Path::new(".")) {
+                Ok(lockfile) => lockfile,
+                Err(err) => {
+                    error!("Unable to parse lockfile", LOCKFILE; err);
+                    return;
+                }
+            };
+
+            if lockfile.configs.is_empty() {
+                warn!("No configs have been cloned into this project");
+                return;
+            }
+
+            let connection = &mut crate::connect_db();
+
+            let mut table = prettytable::Table::new();
+            table.set_titles(prettytable::row![
+                "Shorthand",
+                "Filename",
+                "Cloned Revision",
+                "Stored Revision",
+                "Local Copy"
+            ]);
+
+            for locked in &lockfile.configs {
+                let stored_revision = match configs::dsl::configs
+                    .filter(configs::filename.eq(&locked.filename))
+                    .select(configs::revision)
+                    .first::<i32>(connection)
+                {
+                    Ok(revision) if revision == locked.revision => continue,
+                    Ok(revision) => revision.to_string(),
+                    Err(_) => "Removed".to_string(),
+                };
+
+                let local_copy = match std::fs::read_to_string(&locked.filename) {
+                    Ok(content) if hash(&content) == locked.hash => "Unchanged",
+                    Ok(_) => "Edited",
+                    Err(_) => "Missing",
+                };
+
+                table.add_row(prettytable::row![
+                    locked.shorthand,
+                    locked.filename,
+                    locked.revision,
+                    stored_revision,
+                    local_copy
+                ]);
+            }
+
+            if table.is_empty() {
+                success!("All cloned configs are up to date");
+                return;
+            }
+
+            table.printstd();
+        })
+}
+
+fn upgrade() -> seahorse::Command {
+    seahorse::Command::new("upgrade")
+        .description("Merge changes to stored configuration files into their cloned copies")
+        .usage("nova configs upgrade [...shorthands] [--editor]")
+        .flag(
+            seahorse::Flag::new("editor", seahorse::FlagType::Bool)
+                .description("Open files with merge conflicts in Vim to resolve them"),
+        )
+        .action(|context| {
+            let folder = std::path::Path::new(".");
+            let mut lockfile = match read_lockfile(folder) {
+                Ok(lockfile) => lockfile,
+                Err(err) => {
+                    error!("Unable to parse lockfile", LOCKFILE; err);
+                    return;
+                }
+            };
+
+            for shorthand in &context.args {
+                if lockfile.configs.iter().all(|c| &c.shorthand != shorthand) {
+                    error!("Config was never cloned into this project", shorthand);
+                }
+            }
+
+            let connection = &mut crate::connect_db();
+            let mut upgraded = 0;
+
+            for locked in lockfile.configs.iter_mut() {
+                if !context.args.is_empty() && !context.args.contains(&locked.shorthand) {
+                    continue;
+                }
+
+                let mut config = match configs::dsl::configs
+                    .filter(configs::filename.eq(&locked.filename))
+                    .first::<Config>(connection)
+                {
+                    Ok(config) if config.revision == locked.revision => continue,
+                    Ok(config) => config,
+                    Err(_) => {
+                        warn!("Config no longer exists", locked.filename);
+                        continue;
+                    }
+                };
+
+                config.content = match resolve(connection, &config) {
+                    Ok(content) => content,
+                    Err(err) => {
+                        error!("Unable to resolve config", locked.shorthand; err);
+                        continue;
+                    }
+                };
+
+                let local = match std::fs::read_to_string(&locked.filename) {
+                    Ok(local) => local,
+                    Err(err) => {
+                        error!("Unable to read from file", locked.filename; err);
+                        continue;
+                    }
+                };
+
+                let content = if hash(&local) == locked.hash {
+                    Ok(config.content.clone())
+                } else {
+                    let base = match revisions::dsl::revisions
+                        .filter(revisions::filename.eq(&locked.filename))
+                        .filter(revisions::revision.eq(locked.revision))
+                        .select(revisions::content)
+                        .first::<String>(connection)
+                    {
+                        Ok(base) => base,
+                        Err(_) => {
+                            error!("Unable to find the cloned revision of", locked.filename);
+                            continue;
+                        }
+                    };
+
+                    diffy::merge(&base, &local, &config.content)
+                };
+
+                let (content, conflicted) = match content {
+                    Ok(content) => (content, false),
+                    Err(content) => (content, true),
+                };
+
+                if let Err(err) = std::fs::write(&locked.filename, &content) {
+                    error!("Unable to write to file", locked.filename; err);
+                    continue;
+                }
+
+                if let Err(err) = std::os::unix::fs::chown(&locked.filename, Some(501), Some(20)) {
+                    error!("Unable to change file owner", locked.filename; err);
+                    continue;
+                }
+
+                locked.revision = config.revision;
+                locked.hash = hash(&config.content);
+                upgraded += 1;
+
+                if !conflicted {
+                    success!("Upgraded file", locked.filename);
+                    continue;
+                }
+
+                if !context.bool_flag("editor") {
+                    warn!("Wrote merge conflicts to file", locked.filename);
+                    continue;
+                }
+
+                match std::process::Command::new("/opt/homebrew/bin/nvim")
+                    .arg(&locked.filename)
+                    .status()
+                {
+                    Ok(_) => match std::fs::read_to_string(&locked.filename) {
+                        Ok(content) if content.contains("<<<<<<<") => {
+                            warn!("Unresolved merge conflicts remain in file", locked.filename);
+                        }
+                        Ok(_) => {
+                            success!("Upgraded file", locked.filename);
+                        }
+                        Err(err) => {
+                            error!("Unable to read from file", locked.filename; err);
+                        }
+                    },
+                    Err(err) => {
+                        error!("Unable to run nvim"; err);
+                    }
+                }
+            }
+
+            if upgraded == 0 {
+                success!("All cloned configs are up to date");
+                return;
+            }
+
+            if let Err(err) = write_lockfile(folder, &mut lockfile) {
+                error!("Unable to write to lockfile", LOCKFILE; err);
+            }
+        })
+}
+
+fn hook() -> seahorse::Command {
+    seahorse::Command::new("hook")
+        .description(
+            "Set the command to run after cloning a configuration file, removes it if empty",
+        )
+        .usage("nova configs hook [shorthand] [command]")
+        .action(|context| {
+            let shorthand = match context.args.first() {
+                Some(shorthand) => shorthand,
+                None => {
+                    error!("Please provide a shorthand, then a command");
+                    return;
+                }
+            };
+
+            let hook = context.args[1..].join(" ");
+            let hook = if hook.trim().is_empty() {
+                None
+            } else {
+                Some(hook)
+            };
+
+            match diesel::update(configs::dsl::configs)
+                .filter(configs::shorthand.eq(shorthand))
+                .set(configs::hook.eq(&hook))
+                .execute(&mut crate::connect_db())
+            {
+                Ok(0) => {
+                    error!("Unknown config shorthand", shorthand);
+                }
+                Ok(_) => {
+                    if hook.is_some() {
+                        success!("Set post-clone hook for", shorthand);
+                    } else {
+                        success!("Removed post-clone hook for", shorthand);
+                    }
+                }
+                Err(err) => {
+                    error!("Unable to update config", shorthand; err);
+                }
+            }
+        })
+}
+
+fn describe() -> seahorse::Command {
+    seahorse::Command::new("describe")
+        .description("Set the description of a configuration file, removes it if empty")
+        .usage("nova configs describe [shorthand] [description]")
+        .action(|context| {
+            let shorthand = match context.args.first() {
+                Some(shorthand) => shorthand,
+                None => {
+                    error!("Please provide a shorthand, then a description");
+                    return;
+                }
+            };
+
+            let description = context.args[1..].join(" ");
+            let description = if description.trim().is_empty() {
+                None
+            } else {
+                Some(description)
+            };
+
+            match diesel::update(configs::dsl::configs)
+                .filter(configs::shorthand.eq(shorthand))
+                .set(configs::description.eq(&description))
+                .execute(&mut crate::connect_db())
+            {
+                Ok(0) => {
+                    error!("Unknown config shorthand", shorthand);
+                }
+                Ok(_) => {
+                    if description.is_some() {
+                        success!("Set description for", shorthand);
+                    } else {
+                        success!("Removed description for", shorthand);
+                    }
+                }
+                Err(err) => {
+                    error!("Unable to update config", shorthand; err);
+                }
+            }
+        })
+}
+
 pub fn configs() -> seahorse::Command {
     seahorse::Command::new("configs")
         .description("Manage reusable project configuration files")
         .command(list())
+        .command(grep())
+        .command(show())
         .command(clone())
         .command(vim())
         .command(add())
         .command(remove())
+        .command(hook())
+        .command(describe())
+        .command(export())
+        .command(import())
+        .command(harvest())
+        .command(audit())
+        .command(propagate())
+        .command(outdated())
+        .command(upgrade())
         .action(|context| context.help())
 }
@@ -1,8 +1,12 @@
+mod agent;
+mod apply;
 mod configs;
 mod generate;
 mod secrets;
 mod setup;
 
+pub use agent::agent;
+pub use apply::apply;
 pub use configs::configs;
 pub use generate::generate;
 pub use secrets::secrets;
@@ -1,38 +1,503 @@
 use {
-    crate::{error, models::Secret, schema::secrets, success, warn},
+    crate::{error, models::Secret, schema::secrets, success, vault, warn},
     diesel::prelude::*,
+    std::{
+        io::Write,
+        os::unix::{fs::OpenOptionsExt, fs::PermissionsExt, process::CommandExt},
+    },
 };
 
-struct Location {
-    project: String,
-    folder: Option<String>,
+static MARKER: &str = ".nova";
+static MODE: u32 = 0o600;
+static PATTERNS: [&str; 10] = [
+    ".env*",
+    "*.pem",
+    "*.key",
+    "*.keystore",
+    "*.jks",
+    "*.p12",
+    "google-services.json",
+    "GoogleService-Info.plist",
+    "serviceAccount*.json",
+    "credentials.json",
+];
+static EXCLUDED: [&str; 5] = ["node_modules", "vendor", "Pods", "build", "target"];
+
+pub struct Location {
+    pub project: String,
+    pub root: std::path::PathBuf,
+    pub folder: Option<String>,
+    pub mode: u32,
+    pub patterns: Vec<String>,
+    pub excluded: Vec<String>,
+}
+
+/// Normalises a project relative path, failing if it is absolute or leads outside the project
+pub fn project_path(path: &str) -> Result<String, String> {
+    let mut components = vec![];
+    for component in std::path::Path::new(&path.replace('\\', "/")).components() {
+        match component {
+            std::path::Component::Normal(part) => {
+                components.push(part.to_string_lossy().to_string())
+            }
+            std::path::Component::CurDir => {}
+            std::path::Component::ParentDir => {
+                if components.pop().is_none() {
+                    return Err(format!("Path \"{}\" leads outside of the project", path));
+                }
+            }
+            std::path::Component::RootDir | std::path::Component::Prefix(_) => {
+                return Err(format!("Path \"{}\" is not relative to the project", path));
+            }
+        }
+    }
+
+    if components.is_empty() {
+        return Err(format!("Path \"{}\" is the project root", path));
+    }
+
+    Ok(components.join("/"))
+}
+
+impl Location {
+    pub fn absolute_path(&self, path: &str) -> Result<std::path::PathBuf, String> {
+        Ok(self.root.join(project_path(path)?))
+    }
+
+    /// Turns a path relative to the folder nova was run from into a project relative path
+    pub fn relative_path(&self, path: &str) -> Result<String, String> {
+        project_path(
+            &std::path::Path::new(self.folder.as_deref().unwrap_or_default())
+                .join(path)
+                .to_string_lossy(),
+        )
+    }
+
+    /// Whether a project relative path is inside the folder nova was run from
+    pub fn contains(&self, path: &str) -> bool {
+        match &self.folder {
+            Some(folder) => path.starts_with(&format!("{}/", folder)),
+            None => true,
+        }
+    }
+}
+
+/// The optional content of a .nova marker file, which marks the root of a project
+#[derive(Default, serde::Deserialize)]
+struct Marker {
+    project: Option<String>,
+    #[serde(default)]
+    origin: bool,
+    mode: Option<String>,
+    patterns: Option<Vec<String>>,
+    excluded: Option<Vec<String>>,
+}
+
+/// Normalises a remote URL so that the SSH and HTTPS remotes of a repository are the same
+fn normalise_remote(url: &str) -> String {
+    let url = url.trim();
+    let (url, scp) = match url.split_once("://") {
+        Some((_, url)) => (url, false),
+        None => (url, true),
+    };
+
+    let (host, path) = if scp {
+        url.split_once(':')
+    } else {
+        url.split_once('/')
+    }
+    .unwrap_or((url, ""));
+    let host = host.rsplit('@').next().unwrap();
+    let host = host.split(':').next().unwrap();
+    let path = path.trim_matches('/');
+    let path = path.strip_suffix(".git").unwrap_or(path);
+
+    if path.is_empty() {
+        host.to_lowercase()
+    } else {
+        format!("{}/{}", host, path).to_lowercase()
+    }
+}
+
+/// Finds the project of the current directory, from the nearest .nova marker or the git toplevel
+pub fn locate() -> Result<Location, String> {
+    let cwd = std::env::current_dir().map_err(|err| err.to_string())?;
+
+    let (root, marker) = match cwd.ancestors().find(|folder| folder.join(MARKER).is_file()) {
+        Some(root) => {
+            let marker = std::fs::read_to_string(root.join(MARKER))
+                .map_err(|err| err.to_string())
+                .and_then(|marker| toml::from_str::<Marker>(&marker).map_err(|err| err.to_string()))
+                .map_err(|err| format!("Invalid {} file: {}", MARKER, err))?;
+            (root.to_path_buf(), marker)
+        }
+        None => match crate::git(&cwd, &["rev-parse", "--show-toplevel"]) {
+            Some(root) => (std::path::PathBuf::from(root.trim()), Marker::default()),
+            None => {
+                return Err(format!(
+                    "Not inside a git repository or a folder with a {} file",
+                    MARKER
+                ))
+            }
+        },
+    };
+
+    let project = match marker.project {
+        Some(project) => project,
+        None if marker.origin => normalise_remote(
+            &crate::git(&root, &["remote", "get-url", "origin"])
+                .ok_or("Project has no origin remote")?,
+        ),
+        None => root
+            .file_name()
+            .ok_or("Project root has no folder name")?
+            .to_string_lossy()
+            .to_string(),
+    };
+
+    let mode = match marker.mode {
+        Some(mode) => u32::from_str_radix(mode.trim_start_matches("0o"), 8)
+            .ok()
+            .filter(|mode| *mode <= 0o777)
+            .ok_or(format!(
+                "Invalid secret file mode \"{}\" in {}",
+                mode, MARKER
+            ))?,
+        None => MODE,
+    };
+
+    let folder = cwd
+        .strip_prefix(&root)
+        .ok()
+        .map(|folder| folder.to_string_lossy().replace('\\', "/"))
+        .filter(|folder| !folder.is_empty());
+
+    Ok(Location {
+        project,
+        root,
+        folder,
+        mode,
+        patterns: marker.patterns.unwrap_or_else(default_patterns),
+        excluded: marker.excluded.unwrap_or_else(default_excluded),
+    })
+}
+
+fn project_flag() -> seahorse::Flag {
+    seahorse::Flag::new("project", seahorse::FlagType::String)
+        .description("Use the secrets of another project, as if the current directory was its root")
+        .alias("p")
+}
+
+fn all_flag() -> seahorse::Flag {
+    seahorse::Flag::new("all", seahorse::FlagType::Bool)
+        .description("Include secrets from the whole project instead of only the current folder")
+        .alias("a")
+}
+
+/// Keeps the secrets inside the current folder, unless the --all flag is set
+fn in_scope(context: &seahorse::Context, location: &Location, secrets: Vec<Secret>) -> Vec<Secret> {
+    if context.bool_flag("all") {
+        return secrets;
+    }
+
+    let secrets = secrets
+        .into_iter()
+        .filter(|secret| location.contains(&secret.path))
+        .collect::<Vec<_>>();
+
+    if secrets.is_empty() {
+        if let Some(folder) = &location.folder {
+            warn!(
+                "No secrets found under this folder, use --all for the whole project",
+                folder
+            );
+        }
+    }
+
+    secrets
 }
 
-fn locate() -> Option<Location> {
-    regex::Regex::new(r#"Projects/([^/]*)/?(.+)?"#)
-        .unwrap()
-        .captures(
-            &std::env::current_dir()
-                .unwrap()
-                .to_str()
-                .unwrap()
-                .replace('\\', "/"),
+fn default_patterns() -> Vec<String> {
+    PATTERNS.iter().map(|pattern| pattern.to_string()).collect()
+}
+
+fn default_excluded() -> Vec<String> {
+    EXCLUDED.iter().map(|folder| folder.to_string()).collect()
+}
+
+/// Lists the gitignored files of the project whose filenames look like secrets,
+/// returns None if the project isn't a git repository
+fn secret_files(location: &Location) -> Option<Vec<String>> {
+    let patterns = location
+        .patterns
+        .iter()
+        .filter_map(|pattern| glob::Pattern::new(pattern).ok())
+        .collect::<Vec<_>>();
+
+    // Pathspecs find secrets nested inside ignored folders, except for dependency and build
+    // folders whose packages often ship test keys
+    let pathspecs = location
+        .patterns
+        .iter()
+        .map(|pattern| format!(":(glob)**/{}", pattern))
+        .chain(
+            location
+                .excluded
+                .iter()
+                .map(|folder| format!(":(exclude,glob)**/{}/**", folder)),
         )
-        .map(|captures| Location {
-            project: captures.get(1).unwrap().as_str().into(),
-            folder: captures.get(2).map(|m| m.as_str().to_string()),
+        .collect::<Vec<_>>();
+    let args = [
+        "ls-files",
+        "-z",
+        "--others",
+        "--ignored",
+        "--exclude-standard",
+        "--",
+    ]
+    .into_iter()
+    .chain(pathspecs.iter().map(|pathspec| pathspec.as_str()))
+    .collect::<Vec<_>>();
+    let files = crate::git(&location.root, &args)?;
+
+    Some(
+        files
+            .split('\0')
+            .filter(|path| !path.is_empty())
+            .filter(|path| {
+                let filename = path.rsplit('/').next().unwrap();
+                patterns.iter().any(|pattern| pattern.matches(filename))
+            })
+            .map(|path| path.to_string())
+            .collect(),
+    )
+}
+
+/// Masks everything after the first = or : of a line, so that only keys stay readable
+fn mask(line: &str) -> String {
+    match line.find(['=', ':']) {
+        // Base64 padding, like at the end of PEM lines, would reveal the whole line as a key
+        Some(i) if !line[i..].trim_end().chars().all(|char| char == '=') => {
+            format!("{}****", &line[..=i])
+        }
+        _ => "****".to_string(),
+    }
+}
+
+/// Parses KEY=value lines, returns None if the content doesn't look like a .env file
+fn parse_env(content: &str) -> Option<Vec<(String, String)>> {
+    content
+        .lines()
+        .map(|line| line.trim())
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(|line| {
+            let (key, value) = line
+                .strip_prefix("export ")
+                .unwrap_or(line)
+                .split_once('=')?;
+            let key = key.trim();
+            if key.is_empty() || key.contains(char::is_whitespace) {
+                return None;
+            }
+            Some((key.to_string(), value.trim().to_string()))
         })
+        .collect()
+}
+
+/// Lists the keys of a .env file, or else the lines, that changed locally
+fn diff(stored: &str, local: &str, reveal: bool) -> Vec<String> {
+    let value = |value: &str| {
+        if reveal {
+            value.to_string()
+        } else {
+            "****".to_string()
+        }
+    };
+
+    let mut changes = vec![];
+    if let (Some(stored), Some(local)) = (parse_env(stored), parse_env(local)) {
+        for (key, stored_value) in &stored {
+            match local.iter().find(|(local_key, _)| local_key == key) {
+                Some((_, local_value)) if local_value != stored_value => changes.push(format!(
+                    "~ {}={} -> {}",
+                    key,
+                    value(stored_value),
+                    value(local_value)
+                )),
+                Some(_) => {}
+                None => changes.push(format!("- {}={}", key, value(stored_value))),
+            }
+        }
+
+        for (key, local_value) in &local {
+            if stored.iter().all(|(stored_key, _)| stored_key != key) {
+                changes.push(format!("+ {}={}", key, value(local_value)));
+            }
+        }
+    }
+
+    // Files that aren't .env files, or only differ in comments and formatting
+    if changes.is_empty() {
+        for hunk in diffy::create_patch(stored, local).hunks() {
+            for line in hunk.lines() {
+                let (sign, line) = match line {
+                    diffy::Line::Insert(line) => ('+', line.trim()),
+                    diffy::Line::Delete(line) => ('-', line.trim()),
+                    diffy::Line::Context(_) => continue,
+                };
+                changes.push(format!(
+                    "{} {}",
+                    sign,
+                    if reveal { line.to_string() } else { mask(line) }
+                ));
+            }
+        }
+    }
+
+    changes
+}
+
+/// Finds the project named by the --project flag, or else the project of the current directory
+fn project(context: &seahorse::Context) -> Result<Location, String> {
+    let location = locate();
+    match context.string_flag("project") {
+        Ok(project) => match location {
+            Ok(location) if location.project == project => Ok(location),
+            _ => Ok(Location {
+                project,
+                root: std::env::current_dir().map_err(|err| err.to_string())?,
+                folder: None,
+                mode: MODE,
+                patterns: default_patterns(),
+                excluded: default_excluded(),
+            }),
+        },
+        Err(_) => location,
+    }
+}
+
+/// Writes a secret file with the project's file mode, creating its missing parent folders
+/// with the same owner and refusing to follow symlinks out of the project
+fn write_secret(location: &Location, path: &str, content: &str) -> std::io::Result<()> {
+    let path = location
+        .absolute_path(path)
+        .map_err(std::io::Error::other)?;
+    let parent = path.parent().unwrap();
+    let missing = parent
+        .ancestors()
+        .take_while(|folder| !folder.exists())
+        .collect::<Vec<_>>();
+
+    if location.root.exists() {
+        let existing = parent.ancestors().find(|folder| folder.exists()).unwrap();
+        if !existing
+            .canonicalize()?
+            .starts_with(location.root.canonicalize()?)
+        {
+            return Err(std::io::Error::other("Path leads outside of the project"));
+        }
+    }
+
+    std::fs::create_dir_all(parent)?;
+    for folder in missing {
+        std::os::unix::fs::chown(folder, Some(501), Some(20))?;
+    }
+
+    if path.is_symlink() {
+        return Err(std::io::Error::other("Path is a symlink"));
+    }
+
+    let mut file = std::fs::OpenOptions::new()
+        .write(true)
+        .create(true)
+        .truncate(true)
+        .mode(location.mode)
+        .open(&path)?;
+    file.write_all(content.as_bytes())?;
+    file.set_permissions(std::fs::Permissions::from_mode(location.mode))?;
+    std::os::unix::fs::chown(path, Some(501), Some(20))
+}
+
+#[derive(Default)]
+pub struct CloneOptions {
+    pub force: bool,
+    pub skip_identical: bool,
+    pub dry_run: bool,
+}
+
+#[derive(PartialEq)]
+pub enum Cloned {
+    Written,
+    Identical,
+    Skipped,
+    Failed,
+}
+
+/// Decrypts a secret and writes it back to its original location, unless the local copy is
+/// identical or has been modified
+pub fn clone_secret(
+    location: &Location,
+    secret: &Secret,
+    key: &vault::Key,
+    options: &CloneOptions,
+) -> Cloned {
+    let aad = vault::aad(&secret.project, &secret.path);
+    let content = match vault::decrypt(key, &aad, &secret.content) {
+        Ok(content) => content,
+        Err(err) => {
+            error!("Unable to decrypt secret", &secret.path; err);
+            return Cloned::Failed;
+        }
+    };
+
+    let path = match location.absolute_path(&secret.path) {
+        Ok(path) => path,
+        Err(err) => {
+            error!("Refusing to clone secret", &secret.path; err);
+            return Cloned::Failed;
+        }
+    };
+
+    match std::fs::read_to_string(&path) {
+        Ok(local) if local == content && options.skip_identical => {
+            println!("Identical secret \"{}\"", &secret.path);
+            return Cloned::Identical;
+        }
+        Ok(local) if local != content && !options.force => {
+            warn!(
+                "Skipped modified secret, use --force to overwrite",
+                &secret.path
+            );
+            return Cloned::Skipped;
+        }
+        _ => {}
+    }
+
+    if options.dry_run {
+        println!("Would clone secret \"{}\"", &secret.path);
+        return Cloned::Written;
+    }
+
+    if let Err(err) = write_secret(location, &secret.path, &content) {
+        error!("Unable to write secret", &secret.path; err);
+        return Cloned::Failed;
+    }
+
+    success!("Cloned secret", &secret.path);
+    Cloned::Written
 }
 
 fn list() -> seahorse::Command {
     seahorse::Command::new("list")
         .description("List all secret filenames for a repository without showing the data")
-        .usage("nova secrets list")
-        .action(|_| {
-            let location = match locate() {
-                Some(location) => location,
-                None => {
-                    error!("Invalid project path");
+        .usage("nova secrets list [--project name] [--all]")
+        .flag(project_flag())
+        .flag(all_flag())
+        .action(|context| {
+            let location = match project(context) {
+                Ok(location) => location,
+                Err(err) => {
+                    error!("Unable to find the project"; err);
                     return;
                 }
             };
@@ -48,11 +513,19 @@ fn list() -> seahorse::Command {
                 }
             };
 
+            let secrets = in_scope(context, &location, secrets);
+            if secrets.is_empty() {
+                return;
+            }
+
             let mut table = prettytable::Table::new();
             table.set_titles(prettytable::row!["Path", "Content Length"]);
 
             for secret in secrets {
-                table.add_row(prettytable::row![secret.path, secret.content.len()]);
+                table.add_row(prettytable::row![
+                    secret.path,
+                    vault::content_len(&secret.content)
+                ]);
             }
 
             table.printstd();
@@ -62,19 +535,45 @@ fn list() -> seahorse::Command {
 fn clone() -> seahorse::Command {
     seahorse::Command::new("clone")
         .description("Clone the repository secrets to their original locations")
-        .usage("nova secrets clone")
-        .action(|_| {
-            let location = match locate() {
-                Some(location) => location,
-                None => {
-                    error!("Invalid project path");
+        .usage("nova secrets clone [...paths] [--project name] [--into folder] [--force]")
+        .flag(project_flag())
+        .flag(all_flag())
+        .flag(
+            seahorse::Flag::new("into", seahorse::FlagType::String)
+                .description("Restore the secrets into another folder instead of the project root")
+                .alias("i"),
+        )
+        .flag(
+            seahorse::Flag::new("force", seahorse::FlagType::Bool)
+                .description("Overwrite local secret files that differ from the stored ones")
+                .alias("f"),
+        )
+        .flag(
+            seahorse::Flag::new("skip-identical", seahorse::FlagType::Bool)
+                .description("Leave local secret files that match the stored ones untouched"),
+        )
+        .flag(
+            seahorse::Flag::new("dry-run", seahorse::FlagType::Bool)
+                .description("Show what would be cloned without writing anything")
+                .alias("n"),
+        )
+        .action(|context| {
+            let mut location = match project(context) {
+                Ok(location) => location,
+                Err(err) => {
+                    error!("Unable to find the project"; err);
                     return;
                 }
             };
 
+            if let Ok(into) = context.string_flag("into") {
+                location.root = std::path::PathBuf::from(into);
+            }
+
+            let connection = &mut crate::connect_db();
             let secrets = match secrets::dsl::secrets
                 .filter(secrets::project.eq(&location.project))
-                .get_results::<Secret>(&mut crate::connect_db())
+                .get_results::<Secret>(connection)
             {
                 Ok(secret) => secret,
                 Err(_) => {
@@ -83,42 +582,142 @@ fn clone() -> seahorse::Command {
                 }
             };
 
-            for secret in secrets {
-                let absolute_path = std::path::PathBuf::from("/Users/mac/Projects")
-                    .join(&secret.project)
-                    .join(&secret.path);
+            let mut failed = 0;
+            let secrets = if !context.args.is_empty() {
+                let mut paths = vec![];
+                for path in &context.args {
+                    match location.relative_path(path) {
+                        Ok(path) => paths.push(path),
+                        Err(err) => {
+                            error!("Invalid secret path", path; err);
+                            failed += 1;
+                        }
+                    }
+                }
 
-                if let Err(err) = std::fs::write(&absolute_path, &secret.content) {
-                    error!("Unable to write to file", &secret.path; err);
-                    return;
+                for path in &paths {
+                    if secrets.iter().all(|secret| &secret.path != path) {
+                        error!("No secret found", path);
+                        failed += 1;
+                    }
                 }
 
-                if let Err(err) = std::os::unix::fs::chown(&absolute_path, Some(501), Some(20)) {
-                    error!("Unable to change file owner", &secret.path; err);
+                secrets
+                    .into_iter()
+                    .filter(|secret| paths.contains(&secret.path))
+                    .collect()
+            } else if crate::input::is_interactive() {
+                let secrets = in_scope(context, &location, secrets);
+                if secrets.is_empty() {
                     return;
                 }
 
-                success!("Cloned secret", &secret.path);
+                let items = secrets
+                    .iter()
+                    .map(|secret| {
+                        if location
+                            .absolute_path(&secret.path)
+                            .is_ok_and(|path| path.exists())
+                        {
+                            format!("{} (exists)", secret.path)
+                        } else {
+                            secret.path.clone()
+                        }
+                    })
+                    .collect::<Vec<_>>();
+                let defaults = secrets
+                    .iter()
+                    .map(|secret| {
+                        !location
+                            .absolute_path(&secret.path)
+                            .is_ok_and(|path| path.exists())
+                    })
+                    .collect::<Vec<_>>();
+
+                match crate::input::pick("Secrets to clone", &items, &defaults) {
+                    Some(indexes) => secrets
+                        .into_iter()
+                        .enumerate()
+                        .filter(|(i, _)| indexes.contains(i))
+                        .map(|(_, secret)| secret)
+                        .collect(),
+                    None => {
+                        warn!("No secrets selected");
+                        return;
+                    }
+                }
+            } else {
+                in_scope(context, &location, secrets)
+            };
+
+            if secrets.is_empty() {
+                return;
+            }
+
+            let key = match vault::unlock(connection) {
+                Some(key) => key,
+                None => return,
+            };
+
+            let options = CloneOptions {
+                force: context.bool_flag("force"),
+                skip_identical: context.bool_flag("skip-identical"),
+                dry_run: context.bool_flag("dry-run"),
+            };
+            let (mut cloned, mut identical, mut skipped) = (0, 0, 0);
+
+            for secret in secrets {
+                match clone_secret(&location, &secret, &key, &options) {
+                    Cloned::Written => cloned += 1,
+                    Cloned::Identical => identical += 1,
+                    Cloned::Skipped => skipped += 1,
+                    Cloned::Failed => failed += 1,
+                }
             }
+
+            println!(
+                "{} {}, identical {}, skipped {}, failed {}",
+                if options.dry_run {
+                    "Would clone"
+                } else {
+                    "Cloned"
+                },
+                cloned,
+                identical,
+                skipped,
+                failed
+            );
         })
 }
 
 fn check() -> seahorse::Command {
     seahorse::Command::new("check")
         .description("Check if the secrets are still the same as that in the database")
-        .usage("nova secrets check")
-        .action(|_| {
-            let location = match locate() {
-                Some(location) => location,
-                None => {
-                    error!("Invalid project path");
+        .usage("nova secrets check [--project name] [--all] [--diff] [--reveal]")
+        .flag(project_flag())
+        .flag(all_flag())
+        .flag(
+            seahorse::Flag::new("diff", seahorse::FlagType::Bool)
+                .description("Show the keys or lines of non-identical secrets that changed")
+                .alias("d"),
+        )
+        .flag(
+            seahorse::Flag::new("reveal", seahorse::FlagType::Bool)
+                .description("Show the values in the diff instead of masking them"),
+        )
+        .action(|context| {
+            let location = match project(context) {
+                Ok(location) => location,
+                Err(err) => {
+                    error!("Unable to find the project"; err);
                     return;
                 }
             };
 
+            let connection = &mut crate::connect_db();
             let secrets = match secrets::dsl::secrets
                 .filter(secrets::project.eq(&location.project))
-                .get_results::<Secret>(&mut crate::connect_db())
+                .get_results::<Secret>(connection)
             {
                 Ok(secret) => secret,
                 Err(_) => {
@@ -127,16 +726,54 @@ fn check() -> seahorse::Command {
                 }
             };
 
-            for secret in secrets {
-                let absolute_path = std::path::PathBuf::from("/Users/mac/Projects")
-                    .join(&secret.project)
-                    .join(&secret.path);
-                match std::fs::read_to_string(&absolute_path) {
+            let secrets = in_scope(context, &location, secrets);
+            let unstored = secret_files(&location)
+                .unwrap_or_default()
+                .into_iter()
+                .filter(|path| context.bool_flag("all") || location.contains(path))
+                .filter(|path| secrets.iter().all(|secret| &secret.path != path))
+                .collect::<Vec<_>>();
+
+            // Only ask for the passphrase when there are stored secrets to compare against
+            let key = if secrets.is_empty() {
+                None
+            } else {
+                match vault::unlock(connection) {
+                    Some(key) => Some(key),
+                    None => return,
+                }
+            };
+
+            for secret in &secrets {
+                let key = key.as_ref().unwrap();
+                let aad = vault::aad(&secret.project, &secret.path);
+                let stored = match vault::decrypt(key, &aad, &secret.content) {
+                    Ok(content) => content,
+                    Err(err) => {
+                        error!("Unable to decrypt secret", &secret.path; err);
+                        continue;
+                    }
+                };
+
+                let path = match location.absolute_path(&secret.path) {
+                    Ok(path) => path,
+                    Err(err) => {
+                        error!("Refusing to check secret", &secret.path; err);
+                        continue;
+                    }
+                };
+
+                match std::fs::read_to_string(path) {
                     Ok(content) => {
-                        if content == secret.content {
+                        if content == stored {
                             println!("Identical secret \"{}\"", &secret.path);
                         } else {
                             println!("Non-identical secret \"{}\"", &secret.path);
+                            if context.bool_flag("diff") {
+                                for change in diff(&stored, &content, context.bool_flag("reveal")) {
+                                    println!("    {}", change);
+                                }
+                            }
                         }
                     }
                     Err(_) => {
@@ -144,18 +781,23 @@ fn check() -> seahorse::Command {
                     }
                 }
             }
+
+            for path in unstored {
+                println!("Unstored secret \"{}\"", path);
+            }
         })
 }
 
 fn set() -> seahorse::Command {
     seahorse::Command::new("set")
-        .description("Set a repository secret, update if it already exists")
-        .usage("nova secrets set [path/to/config]")
+        .description("Set a repository secret from a file or stdin, update if it already exists")
+        .usage("nova secrets set [path/to/config] [-] [--project name]")
+        .flag(project_flag())
         .action(|context| {
-            let location = match locate() {
-                Some(location) => location,
-                None => {
-                    error!("Invalid project path");
+            let location = match project(context) {
+                Ok(location) => location,
+                Err(err) => {
+                    error!("Unable to find the project"; err);
                     return;
                 }
             };
@@ -168,37 +810,50 @@ fn set() -> seahorse::Command {
                 }
             };
 
-            let content = match std::fs::read_to_string(&cwd_relative_path) {
+            let project_relative_path = match location.relative_path(&cwd_relative_path) {
+                Ok(path) => path,
+                Err(err) => {
+                    error!("Invalid secret path", cwd_relative_path; err);
+                    return;
+                }
+            };
+
+            // Escalating to sudo restarts nova, so connect before reading from stdin
+            let connection = &mut crate::connect_db();
+            let key = match vault::unlock(connection) {
+                Some(key) => key,
+                None => return,
+            };
+
+            let path = context.args.get(1).unwrap_or(&cwd_relative_path);
+            let content = match crate::input::read_content(path) {
                 Ok(content) => content,
                 Err(err) => {
-                    error!("Unable to read from file", cwd_relative_path; err);
+                    error!("Unable to read from file", path; err);
                     return;
                 }
             };
 
+            let aad = vault::aad(&location.project, &project_relative_path);
             let secret = Secret {
                 project: location.project,
-                path: std::path::PathBuf::from(&location.folder.unwrap_or_default())
-                    .join(&cwd_relative_path)
-                    .to_str()
-                    .unwrap()
-                    .into(),
-                content,
+                path: project_relative_path,
+                content: vault::encrypt(&key, &aad, &content),
             };
 
             let upsert = match secrets::dsl::secrets
                 .filter(secrets::project.eq(&secret.project))
                 .filter(secrets::path.eq(&secret.path))
-                .first::<Secret>(&mut crate::connect_db())
+                .first::<Secret>(connection)
             {
                 Ok(_) => diesel::update(secrets::dsl::secrets)
                     .filter(secrets::project.eq(&secret.project))
                     .filter(secrets::path.eq(&secret.path))
                     .set(secrets::content.eq(&secret.content))
-                    .execute(&mut crate::connect_db()),
+                    .execute(connection),
                 Err(_) => diesel::insert_into(secrets::dsl::secrets)
                     .values(&secret)
-                    .execute(&mut crate::connect_db()),
+                    .execute(connection),
             };
 
             match upsert {
@@ -212,15 +867,117 @@ fn set() -> seahorse::Command {
         })
 }
 
+fn scan() -> seahorse::Command {
+    seahorse::Command::new("scan")
+        .description(
+            "Find gitignored files that look like secrets but aren't stored, and store them",
+        )
+        .usage("nova secrets scan [--project name] [--yes]")
+        .flag(project_flag())
+        .flag(
+            seahorse::Flag::new("yes", seahorse::FlagType::Bool)
+                .description("Store the unstored secret files without asking for confirmation")
+                .alias("y"),
+        )
+        .action(|context| {
+            let location = match project(context) {
+                Ok(location) => location,
+                Err(err) => {
+                    error!("Unable to find the project"; err);
+                    return;
+                }
+            };
+
+            let files = match secret_files(&location) {
+                Some(files) => files,
+                None => {
+                    error!("Unable to list ignored files, the project is not a git repository");
+                    return;
+                }
+            };
+
+            let connection = &mut crate::connect_db();
+            let stored = match secrets::dsl::secrets
+                .filter(secrets::project.eq(&location.project))
+                .select(secrets::path)
+                .load::<String>(connection)
+            {
+                Ok(stored) => stored,
+                Err(err) => {
+                    error!("Unable to fetch secrets"; err);
+                    return;
+                }
+            };
+
+            let unstored = files
+                .into_iter()
+                .filter(|path| !stored.contains(path))
+                .collect::<Vec<_>>();
+
+            if unstored.is_empty() {
+                success!("All secret files are stored");
+                return;
+            }
+
+            for path in &unstored {
+                println!("Unstored secret \"{}\"", path);
+            }
+
+            let store = context.bool_flag("yes")
+                || crate::input::is_interactive()
+                    && crate::input::confirm(&format!("Store {} secret file(s)?", unstored.len()));
+            if !store {
+                return;
+            }
+
+            let key = match vault::unlock(connection) {
+                Some(key) => key,
+                None => return,
+            };
+
+            let mut secrets = vec![];
+            for path in unstored {
+                let content = match location.absolute_path(&path).and_then(|absolute_path| {
+                    std::fs::read_to_string(absolute_path).map_err(|err| err.to_string())
+                }) {
+                    Ok(content) => content,
+                    Err(err) => {
+                        error!("Unable to read from file", path; err);
+                        return;
+                    }
+                };
+
+                secrets.push(Secret {
+                    project: location.project.clone(),
+                    content: vault::encrypt(&key, &vault::aad(&location.project, &path), &content),
+                    path,
+                });
+            }
+
+            match diesel::insert_into(secrets::dsl::secrets)
+                .values(&secrets)
+                .execute(connection)
+            {
+                Ok(stored) => {
+                    success!(format!("Stored {} secret file(s)", stored));
+                }
+                Err(err) => {
+                    error!("Unable to store secrets"; err);
+                }
+            }
+        })
+}
+
 fn remove() -> seahorse::Command {
     seahorse::Command::new("remove")
         .description("Remove a repository secret")
-        .usage("nova secrts remove [path/to/config]")
+        .usage("nova secrets remove [path/to/config] [--project name]")
+        .flag(project_flag())
         .action(|context| {
-            let location = match locate() {
-                Some(location) => location,
-                None => {
-                    error!("Invalid project path");
+            let location = match project(context) {
+                Ok(location) => location,
+                Err(err) => {
+                    error!("Unable to find the project"; err);
                     return;
                 }
             };
@@ -233,12 +990,13 @@ fn remove() -> seahorse::Command {
                 }
             };
 
-            let project_relative_path: String =
-                std::path::PathBuf::from(&location.folder.unwrap_or_default())
-                    .join(cwd_relative_path)
-                    .to_str()
-                    .unwrap()
-                    .into();
+            let project_relative_path = match location.relative_path(&cwd_relative_path) {
+                Ok(path) => path,
+                Err(err) => {
+                    error!("Invalid secret path", cwd_relative_path; err);
+                    return;
+                }
+            };
 
             match diesel::delete(secrets::dsl::secrets)
                 .filter(secrets::project.eq(&location.project))
@@ -259,13 +1017,303 @@ fn remove() -> seahorse::Command {
         })
 }
 
+fn init() -> seahorse::Command {
+    seahorse::Command::new("init")
+        .description("Set the master passphrase and encrypt all stored secrets with it")
+        .usage("nova secrets init")
+        .action(|_| {
+            let connection = &mut crate::connect_db();
+            if vault::exists(connection) {
+                error!("Secrets are already encrypted");
+                return;
+            }
+
+            let passphrase = match vault::new_passphrase() {
+                Some(passphrase) => passphrase,
+                None => return,
+            };
+
+            let (stored_vault, key) = match vault::create(&passphrase) {
+                Ok(created) => created,
+                Err(err) => {
+                    error!("Unable to derive key from passphrase"; err);
+                    return;
+                }
+            };
+
+            let encrypted = connection.transaction::<_, diesel::result::Error, _>(|connection| {
+                vault::store(connection, &stored_vault)?;
+
+                let mut encrypted = 0;
+                for secret in secrets::dsl::secrets.load::<Secret>(connection)? {
+                    if vault::is_encrypted(&secret.content) {
+                        continue;
+                    }
+
+                    let aad = vault::aad(&secret.project, &secret.path);
+                    diesel::update(secrets::dsl::secrets)
+                        .filter(secrets::project.eq(&secret.project))
+                        .filter(secrets::path.eq(&secret.path))
+                        .set(secrets::content.eq(vault::encrypt(&key, &aad, &secret.content)))
+                        .execute(connection)?;
+                    encrypted += 1;
+                }
+
+                Ok(encrypted)
+            });
+
+            match encrypted {
+                Ok(encrypted) => {
+                    success!(format!("Encrypted {} stored secret(s)", encrypted));
+                }
+                Err(err) => {
+                    error!("Unable to encrypt secrets"; err);
+                }
+            }
+        })
+}
+
+fn rekey() -> seahorse::Command {
+    seahorse::Command::new("rekey")
+        .description("Change the master passphrase and re-encrypt all stored secrets with it")
+        .usage("nova secrets rekey")
+        .action(|_| {
+            let connection = &mut crate::connect_db();
+            let old_key = match vault::unlock_with_passphrase(connection) {
+                Some(key) => key,
+                None => return,
+            };
+
+            let passphrase = match vault::new_passphrase() {
+                Some(passphrase) => passphrase,
+                None => return,
+            };
+
+            let (stored_vault, new_key) = match vault::create(&passphrase) {
+                Ok(created) => created,
+                Err(err) => {
+                    error!("Unable to derive key from passphrase"; err);
+                    return;
+                }
+            };
+
+            let rekeyed =
+                connection.transaction::<_, Box<dyn std::error::Error>, _>(|connection| {
+                    let mut contents = vec![];
+                    for secret in secrets::dsl::secrets.load::<Secret>(connection)? {
+                        let aad = vault::aad(&secret.project, &secret.path);
+                        let content =
+                            vault::decrypt(&old_key, &aad, &secret.content).map_err(|err| {
+                                format!("Unable to decrypt \"{}\": {}", secret.path, err)
+                            })?;
+
+                        diesel::update(secrets::dsl::secrets)
+                            .filter(secrets::project.eq(&secret.project))
+                            .filter(secrets::path.eq(&secret.path))
+                            .set(secrets::content.eq(vault::encrypt(&new_key, &aad, &content)))
+                            .execute(connection)?;
+                        contents.push((secret.project, secret.path, content));
+                    }
+
+                    vault::store(connection, &stored_vault)?;
+
+                    // Only commit if every row reads back and decrypts with the new key
+                    for (project, path, content) in &contents {
+                        let stored = secrets::dsl::secrets
+                            .filter(secrets::project.eq(project))
+                            .filter(secrets::path.eq(path))
+                            .select(secrets::content)
+                            .first::<String>(connection)?;
+
+                        let aad = vault::aad(project, path);
+                        if vault::decrypt(&new_key, &aad, &stored).as_ref() != Ok(content) {
+                            return Err(format!("Unable to verify \"{}\"", path).into());
+                        }
+                    }
+
+                    Ok(contents.len())
+                });
+
+            match rekeyed {
+                Ok(rekeyed) => {
+                    success!(format!("Re-encrypted {} stored secret(s)", rekeyed));
+
+                    // Keep an unlocked agent usable instead of leaving it with the old key
+                    if vault::agent_request("GET").is_some_and(|r| r.starts_with("KEY ")) {
+                        vault::agent_request(&format!("SET {}", vault::encode_key(&new_key)));
+                    }
+                }
+                Err(err) => {
+                    error!("Unable to rekey secrets, nothing was changed"; err);
+                }
+            }
+        })
+}
+
+fn unlock() -> seahorse::Command {
+    seahorse::Command::new("unlock")
+        .description("Unlock secrets in the agent so the passphrase isn't asked for every command")
+        .usage("nova secrets unlock")
+        .action(|_| {
+            let connection = &mut crate::connect_db();
+            let key = match vault::unlock(connection) {
+                Some(key) => key,
+                None => return,
+            };
+
+            if vault::agent_request("PING").is_none() {
+                let spawned = std::env::current_exe().and_then(|nova| {
+                    crate::owner_command(nova)
+                        .arg("agent")
+                        .stdin(std::process::Stdio::null())
+                        .stdout(std::process::Stdio::null())
+                        .stderr(std::process::Stdio::null())
+                        .process_group(0)
+                        .spawn()
+                });
+
+                if let Err(err) = spawned {
+                    error!("Unable to start the agent"; err);
+                    return;
+                }
+
+                for _ in 0..50 {
+                    if vault::agent_request("PING").is_some() {
+                        break;
+                    }
+                    std::thread::sleep(std::time::Duration::from_millis(100));
+                }
+            }
+
+            match vault::agent_request(&format!("SET {}", vault::encode_key(&key))).as_deref() {
+                Some("OK") => {
+                    success!("Unlocked secrets until the agent is unused for a while");
+                }
+                _ => {
+                    error!("Unable to reach the agent on", vault::AGENT_SOCKET);
+                }
+            }
+        })
+}
+
+fn lock() -> seahorse::Command {
+    seahorse::Command::new("lock")
+        .description("Make the agent forget the secrets key")
+        .usage("nova secrets lock")
+        .action(|_| {
+            if sudo::escalate_if_needed().is_err() {
+                error!("Sudo permission required to access the agent");
+                return;
+            }
+
+            match vault::agent_request("LOCK") {
+                Some(_) => {
+                    success!("Locked secrets");
+                }
+                None => {
+                    warn!("Agent is not running, secrets are already locked");
+                }
+            }
+        })
+}
+
 pub fn secrets() -> seahorse::Command {
     seahorse::Command::new("secrets")
         .description("Manage secrets for different repositories")
+        .command(init())
+        .command(rekey())
+        .command(unlock())
+        .command(lock())
         .command(list())
         .command(clone())
         .command(check())
         .command(set())
+        .command(scan())
         .command(remove())
         .action(|context| context.help())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn project_path_normalises_relative_paths() {
+        assert_eq!(project_path("a/../b"), Ok("b".to_string()));
+        assert_eq!(project_path("./a//b/"), Ok("a/b".to_string()));
+        assert_eq!(project_path("a\\b"), Ok("a/b".to_string()));
+    }
+
+    #[test]
+    fn project_path_rejects_paths_outside_the_project() {
+        assert!(project_path("..").is_err());
+        assert!(project_path("a/../../b").is_err());
+        assert!(project_path("/etc/passwd").is_err());
+        assert!(project_path("").is_err());
+        assert!(project_path("a/..").is_err());
+    }
+
+    #[test]
+    fn parse_env_reads_only_env_files() {
+        assert_eq!(
+            parse_env("# comment\nexport A=1\n\nB = two words\n"),
+            Some(vec![
+                ("A".to_string(), "1".to_string()),
+                ("B".to_string(), "two words".to_string())
+            ])
+        );
+        assert_eq!(parse_env("{\n\t\"a\": 1\n}"), None);
+    }
+
+    #[test]
+    fn diff_masks_env_values() {
+        let changes = diff(
+            "A=old1\nB=same\nC=gone1\n",
+            "A=new1\nB=same\nD=added1\n",
+            false,
+        );
+        assert_eq!(changes, ["~ A=**** -> ****", "- C=****", "+ D=****"]);
+
+        let changes = diff("A=old1\n", "A=new1\n", true);
+        assert_eq!(changes, ["~ A=old1 -> new1"]);
+    }
+
+    #[test]
+    fn diff_masks_lines_of_other_files() {
+        let stored = "{\n\t\"private_key\": \"old1\",\n\t\"id\": 1\n}\n";
+        let local = "{\n\t\"private_key\": \"new1\",\n\t\"id\": 1\n}\n";
+        assert_eq!(
+            diff(stored, local, false),
+            ["- \"private_key\":****", "+ \"private_key\":****"]
+        );
+
+        let stored = "-----BEGIN KEY-----\nMIIBold1\nb2xkMQ==\n-----END KEY-----\n";
+        let local = "-----BEGIN KEY-----\nMIIBnew1\nbmV3MQ=\n-----END KEY-----\n";
+        for change in diff(stored, local, false) {
+            assert!(["old1", "new1", "b2xkMQ", "bmV3MQ"]
+                .iter()
+                .all(|value| !change.contains(value)));
+        }
+    }
+
+    #[test]
+    fn normalise_remote_gives_one_key_per_repository() {
+        let key = "github.com/zs1l3nt/js-node-nova";
+        assert_eq!(
+            normalise_remote("git@github.com:zS1L3NT/js-node-nova.git"),
+            key
+        );
+        assert_eq!(
+            normalise_remote("https://github.com/zS1L3NT/js-node-nova"),
+            key
+        );
+        assert_eq!(
+            normalise_remote("https://user@github.com/zS1L3NT/js-node-nova.git/"),
+            key
+        );
+        assert_eq!(
+            normalise_remote("ssh://git@github.com:22/zS1L3NT/js-node-nova.git"),
+            key
+        );
+    }
+}
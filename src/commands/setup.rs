@@ -2,7 +2,7 @@ use crate::{error, success, warn};
 
 static AUTHOR: &'static str = "zS1L3NT <dev@zectan.com> (https://www.zectan.com)";
 static LICENSE: &'static str = "GPL-3.0";
-static SCRIPT_LINT: &'static str =
+pub static SCRIPT_LINT: &'static str =
     "tsc --noEmit && rm tsconfig.tsbuildinfo && eslint src --fix && prettier src --write";
 static DEV_DEPENDENCIES: [&'static str; 10] = [
     "@typescript-eslint/eslint-plugin",
@@ -0,0 +1,210 @@
+#[derive(Clone, Copy, PartialEq)]
+pub enum Format {
+    Json,
+    Yaml,
+    Toml,
+}
+
+impl Format {
+    pub fn from_filename(filename: &str) -> Option<Format> {
+        Format::from_extension(std::path::Path::new(filename).extension()?.to_str()?)
+    }
+
+    pub fn from_extension(extension: &str) -> Option<Format> {
+        match extension.to_lowercase().as_str() {
+            "json" => Some(Format::Json),
+            "yaml" | "yml" => Some(Format::Yaml),
+            "toml" => Some(Format::Toml),
+            _ => None,
+        }
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Format::Json => "json",
+            Format::Yaml => "yaml",
+            Format::Toml => "toml",
+        }
+    }
+
+    /// Parses content into a JSON value, failing on values that JSON can't hold without changes
+    pub fn parse(&self, content: &str) -> Result<serde_json::Value, String> {
+        match self {
+            Format::Json => serde_json::from_str(content).map_err(|err| err.to_string()),
+            Format::Yaml => {
+                check_yaml(&serde_yaml::from_str(content).map_err(|err| err.to_string())?)?;
+                serde_yaml::from_str(content).map_err(|err| err.to_string())
+            }
+            Format::Toml => {
+                check_toml(&toml::from_str(content).map_err(|err| err.to_string())?)?;
+                toml::from_str(content).map_err(|err| err.to_string())
+            }
+        }
+    }
+
+    /// Checks for comments outside of quoted strings, which are lost when the content is parsed
+    pub fn has_comments(&self, content: &str) -> bool {
+        let mut chars = content.chars().peekable();
+        let mut quote = None;
+        let mut previous = '\n';
+
+        while let Some(char) = chars.next() {
+            match (quote, char) {
+                (Some('"'), '\\') => {
+                    chars.next();
+                }
+                (Some('\''), '\'') if chars.peek() == Some(&'\'') => {
+                    chars.next();
+                }
+                (Some(q), _) if char == q => quote = None,
+                (Some(_), _) => {}
+                // Quotes only start a string at the start of a value, so "don't" isn't one
+                (None, '"' | '\'') if previous.is_whitespace() || ":=[{,\"".contains(previous) => {
+                    quote = Some(char)
+                }
+                (None, '#') if *self != Format::Json => return true,
+                (None, '/') if *self == Format::Json && matches!(chars.peek(), Some('/' | '*')) => {
+                    return true
+                }
+                (None, _) => {}
+            }
+            previous = char;
+        }
+
+        false
+    }
+
+    pub fn serialize(&self, value: &serde_json::Value) -> Result<String, String> {
+        match self {
+            Format::Json => {
+                let mut output = vec![];
+                let mut serializer = serde_json::Serializer::with_formatter(
+                    &mut output,
+                    serde_json::ser::PrettyFormatter::with_indent(b"\t"),
+                );
+                serde::Serialize::serialize(value, &mut serializer)
+                    .map_err(|err| err.to_string())?;
+                Ok(String::from_utf8(output).unwrap() + "\n")
+            }
+            Format::Yaml => serde_yaml::to_string(value).map_err(|err| err.to_string()),
+            Format::Toml => toml::to_string_pretty(value).map_err(|err| err.to_string()),
+        }
+    }
+}
+
+fn check_yaml(value: &serde_yaml::Value) -> Result<(), String> {
+    match value {
+        serde_yaml::Value::Number(number) if number.as_f64().is_some_and(|n| !n.is_finite()) => {
+            Err(format!("Unsupported value {}", number))
+        }
+        serde_yaml::Value::Sequence(values) => values.iter().try_for_each(check_yaml),
+        serde_yaml::Value::Mapping(mapping) => mapping
+            .iter()
+            .try_for_each(|(key, value)| check_yaml(key).and_then(|_| check_yaml(value))),
+        serde_yaml::Value::Tagged(tagged) => Err(format!("Unsupported tag {}", tagged.tag)),
+        _ => Ok(()),
+    }
+}
+
+fn check_toml(value: &toml::Value) -> Result<(), String> {
+    match value {
+        toml::Value::Float(float) if !float.is_finite() => {
+            Err(format!("Unsupported value {}", float))
+        }
+        toml::Value::Datetime(datetime) => Err(format!("Unsupported datetime {}", datetime)),
+        toml::Value::Array(values) => values.iter().try_for_each(check_toml),
+        toml::Value::Table(table) => table.values().try_for_each(check_toml),
+        _ => Ok(()),
+    }
+}
+
+/// Converts content to another format, failing if the content would not survive the conversion
+pub fn convert(content: &str, from: Format, to: Format) -> Result<String, String> {
+    if from.has_comments(content) {
+        return Err("Comments would be lost in the conversion".to_string());
+    }
+
+    let value = from.parse(content)?;
+    let converted = to.serialize(&value)?;
+    if to.parse(&converted)? != value {
+        return Err(format!(
+            "Content cannot be represented in {} without changes",
+            to.extension()
+        ));
+    }
+
+    Ok(converted)
+}
+
+/// Applies a JSON merge patch (RFC 7396), where objects are merged and null removes a key
+pub fn merge(base: &mut serde_json::Value, patch: serde_json::Value) {
+    let patch = match patch {
+        serde_json::Value::Object(patch) => patch,
+        patch => {
+            *base = patch;
+            return;
+        }
+    };
+
+    if !base.is_object() {
+        *base = serde_json::Value::Object(serde_json::Map::new());
+    }
+
+    let base = base.as_object_mut().unwrap();
+    for (key, value) in patch {
+        if value.is_null() {
+            base.shift_remove(&key);
+        } else {
+            merge(base.entry(key).or_insert(serde_json::Value::Null), value);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn convert_keeps_plain_values_in_order() {
+        let converted = convert("b = 1\na = \"x\"\n", Format::Toml, Format::Json);
+        assert_eq!(
+            converted,
+            Ok("{\n\t\"b\": 1,\n\t\"a\": \"x\"\n}\n".to_string())
+        );
+    }
+
+    #[test]
+    fn convert_rejects_values_json_cannot_hold() {
+        for format in [Format::Json, Format::Yaml] {
+            assert!(convert("date = 1979-05-27T07:32:00Z", Format::Toml, format).is_err());
+            assert!(convert("float = nan", Format::Toml, format).is_err());
+            assert!(convert("float = inf", Format::Toml, format).is_err());
+        }
+
+        assert!(convert("float: .nan", Format::Yaml, Format::Json).is_err());
+        assert!(convert("float: .inf", Format::Yaml, Format::Toml).is_err());
+        assert!(convert("value: !secret abc", Format::Yaml, Format::Json).is_err());
+    }
+
+    #[test]
+    fn has_comments_finds_comments_outside_strings() {
+        assert!(Format::Yaml.has_comments("# comment\na: 1"));
+        assert!(Format::Yaml.has_comments("a: 1 # important"));
+        assert!(Format::Toml.has_comments("key = 1 # note"));
+        assert!(Format::Toml.has_comments("key = \"a\" # note"));
+        assert!(Format::Json.has_comments("{\n\t// comment\n\t\"a\": 1\n}"));
+        assert!(Format::Json.has_comments("{\"a\": 1 /* comment */}"));
+    }
+
+    #[test]
+    fn has_comments_ignores_strings() {
+        assert!(!Format::Yaml.has_comments("a: \"x # not\""));
+        assert!(!Format::Yaml.has_comments("a: 'x # it''s not'"));
+        assert!(!Format::Yaml.has_comments("a: don't\nb: \"# not\""));
+        assert!(!Format::Toml.has_comments("key = 'x # not'"));
+        assert!(!Format::Toml.has_comments("key = \"\"\"\nx # not\n\"\"\""));
+        assert!(!Format::Toml.has_comments("key = \"\\\" # not\""));
+        assert!(!Format::Json.has_comments("{\"url\": \"https://example.com\"}"));
+        assert!(!Format::Json.has_comments("{\"a\": \"#\"}"));
+    }
+}
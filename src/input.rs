@@ -0,0 +1,68 @@
+pub fn prompt(message: &str) -> String {
+    let mut input = String::new();
+    print!("{}", message);
+    std::io::Write::flush(&mut std::io::stdout()).unwrap();
+    std::io::stdin().read_line(&mut input).unwrap();
+    input.trim().to_string()
+}
+
+/// Reads the content of a file, or of stdin if the path is "-"
+pub fn read_content(path: &str) -> std::io::Result<String> {
+    if path == "-" {
+        std::io::read_to_string(std::io::stdin())
+    } else {
+        std::fs::read_to_string(path)
+    }
+}
+
+pub fn confirm(message: &str) -> bool {
+    matches!(
+        prompt(&format!("{} [y/N] ", message))
+            .to_lowercase()
+            .as_str(),
+        "y" | "yes"
+    )
+}
+
+/// Shows a multi-select list in the terminal, returns None if the user cancelled
+pub fn pick(message: &str, items: &[String], defaults: &[bool]) -> Option<Vec<usize>> {
+    dialoguer::MultiSelect::new()
+        .with_prompt(message)
+        .items(items)
+        .defaults(defaults)
+        .interact_opt()
+        .ok()
+        .flatten()
+}
+
+pub fn is_interactive() -> bool {
+    std::io::IsTerminal::is_terminal(&std::io::stdin())
+}
+
+/// Parses a selection like "1,3-5" into zero-based indexes, an empty selection selects everything
+pub fn select(selection: &str, count: usize) -> Option<Vec<usize>> {
+    if selection.is_empty() {
+        return Some((0..count).collect());
+    }
+
+    let mut indexes = vec![];
+    for part in selection.split(',').map(|part| part.trim()) {
+        let (start, end) = match part.split_once('-') {
+            Some((start, end)) => (
+                start.trim().parse::<usize>().ok()?,
+                end.trim().parse().ok()?,
+            ),
+            None => (part.parse::<usize>().ok()?, part.parse().ok()?),
+        };
+
+        if start == 0 || start > end || end > count {
+            return None;
+        }
+
+        indexes.extend((start - 1)..end);
+    }
+
+    indexes.sort();
+    indexes.dedup();
+    Some(indexes)
+}
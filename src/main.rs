@@ -1,20 +1,71 @@
 mod commands;
+mod formats;
+mod input;
 mod models;
 mod output;
 mod schema;
+mod vault;
+
+use std::os::unix::process::CommandExt;
+
+const MIGRATIONS: diesel_migrations::EmbeddedMigrations = diesel_migrations::embed_migrations!();
 
 pub fn connect_db() -> diesel::SqliteConnection {
     if sudo::escalate_if_needed().is_err() {
         panic!("Sudo permission required to access secrets");
     }
 
-    <diesel::SqliteConnection as diesel::Connection>::establish("file:/Users/mac/nova.db")
-        .unwrap_or_else(|_| panic!("Error connecting to /Users/mac/nova.db"))
+    let mut connection =
+        <diesel::SqliteConnection as diesel::Connection>::establish("file:/Users/mac/nova.db")
+            .unwrap_or_else(|_| panic!("Error connecting to /Users/mac/nova.db"));
+
+    diesel_migrations::MigrationHarness::run_pending_migrations(&mut connection, MIGRATIONS)
+        .unwrap_or_else(|_| panic!("Error migrating /Users/mac/nova.db"));
+
+    connection
+}
+
+pub fn git(folder: &std::path::Path, args: &[&str]) -> Option<String> {
+    std::process::Command::new("git")
+        .arg("-C")
+        .arg(folder)
+        .args(args)
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .map(|output| String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+/// Creates a command that runs as the owner of the projects instead of root, with their home
+/// folder so that tools use their config instead of root's
+pub fn owner_command(program: impl AsRef<std::ffi::OsStr>) -> std::process::Command {
+    let mut command = std::process::Command::new(program);
+    command
+        .env("HOME", "/Users/mac")
+        .env("USER", "mac")
+        .env("LOGNAME", "mac")
+        .uid(501)
+        .gid(20);
+    command
+}
+
+/// Runs git as the owner of the projects, for commands that write to the repository
+pub fn git_as_user(folder: &std::path::Path, args: &[&str]) -> Option<String> {
+    owner_command("git")
+        .arg("-C")
+        .arg(folder)
+        .args(args)
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .map(|output| String::from_utf8_lossy(&output.stdout).to_string())
 }
 
 fn main() {
     let app = seahorse::App::new("nova")
         .description("A CLI for helping me with various tasks")
+        .command(commands::agent())
+        .command(commands::apply())
         .command(commands::configs())
         .command(commands::generate())
         .command(commands::secrets())
@@ -6,6 +6,18 @@ pub struct Config {
     pub filename: String,
     pub shorthand: String,
     pub content: String,
+    pub revision: i32,
+    pub hook: Option<String>,
+    pub base: Option<String>,
+    pub description: Option<String>,
+}
+
+#[derive(Queryable, Insertable)]
+#[diesel(table_name = super::schema::revisions)]
+pub struct Revision {
+    pub filename: String,
+    pub revision: i32,
+    pub content: String,
 }
 
 #[derive(Queryable, Insertable)]
@@ -15,3 +27,11 @@ pub struct Secret {
     pub path: String,
     pub content: String,
 }
+
+#[derive(Queryable, Insertable)]
+#[diesel(table_name = super::schema::vault)]
+pub struct Vault {
+    pub id: i32,
+    pub salt: String,
+    pub verifier: String,
+}
@@ -5,6 +5,18 @@ diesel::table! {
         filename -> Text,
         shorthand -> Text,
         content -> Text,
+        revision -> Integer,
+        hook -> Nullable<Text>,
+        base -> Nullable<Text>,
+        description -> Nullable<Text>,
+    }
+}
+
+diesel::table! {
+    revisions (filename, revision) {
+        filename -> Text,
+        revision -> Integer,
+        content -> Text,
     }
 }
 
@@ -16,4 +28,12 @@ diesel::table! {
     }
 }
 
-diesel::allow_tables_to_appear_in_same_query!(configs, secrets);
+diesel::table! {
+    vault (id) {
+        id -> Integer,
+        salt -> Text,
+        verifier -> Text,
+    }
+}
+
+diesel::allow_tables_to_appear_in_same_query!(configs, revisions, secrets, vault);
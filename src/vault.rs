@@ -0,0 +1,285 @@
+use {
+    crate::{error, models::Vault, schema::vault},
+    base64::Engine,
+    chacha20poly1305::{
+        aead::{rand_core::RngCore, Aead, AeadCore, KeyInit, OsRng, Payload},
+        XChaCha20Poly1305, XNonce,
+    },
+    diesel::prelude::*,
+    std::io::{BufRead, Write},
+};
+
+pub static AGENT_SOCKET: &str = "/Users/mac/.nova/agent.sock";
+static PREFIX: &str = "nova:v1:";
+static VERIFIER: &str = "nova";
+static VERIFIER_AAD: &str = "vault";
+
+pub type Key = [u8; 32];
+
+/// Derives the key used to encrypt secrets from the master passphrase with Argon2id
+fn derive_key(passphrase: &str, salt: &str) -> Result<Key, String> {
+    let salt = base64::engine::general_purpose::STANDARD
+        .decode(salt)
+        .map_err(|err| err.to_string())?;
+
+    let mut key = [0; 32];
+    argon2::Argon2::default()
+        .hash_password_into(passphrase.as_bytes(), &salt, &mut key)
+        .map_err(|err| err.to_string())?;
+    Ok(key)
+}
+
+pub fn is_encrypted(content: &str) -> bool {
+    content.starts_with(PREFIX)
+}
+
+/// Returns the associated data that binds the ciphertext of a secret to its row, so it can't be
+/// moved to another project or path without failing to decrypt
+pub fn aad(project: &str, path: &str) -> String {
+    format!("{}\0{}", project, path)
+}
+
+/// Encrypts content with XChaCha20-Poly1305, storing the random nonce in front of the ciphertext
+pub fn encrypt(key: &Key, aad: &str, content: &str) -> String {
+    let nonce = XChaCha20Poly1305::generate_nonce(&mut OsRng);
+    let payload = Payload {
+        msg: content.as_bytes(),
+        aad: aad.as_bytes(),
+    };
+    let ciphertext = XChaCha20Poly1305::new(key.into())
+        .encrypt(&nonce, payload)
+        .expect("Secret content is too large to encrypt");
+
+    PREFIX.to_string()
+        + &base64::engine::general_purpose::STANDARD
+            .encode([nonce.as_slice(), &ciphertext].concat())
+}
+
+pub fn decrypt(key: &Key, aad: &str, content: &str) -> Result<String, String> {
+    let data = base64::engine::general_purpose::STANDARD
+        .decode(
+            content
+                .strip_prefix(PREFIX)
+                .ok_or("Content is not encrypted")?,
+        )
+        .map_err(|err| err.to_string())?;
+
+    if data.len() < 24 {
+        return Err("Content is too short to be encrypted".to_string());
+    }
+
+    let (nonce, ciphertext) = data.split_at(24);
+    let payload = Payload {
+        msg: ciphertext,
+        aad: aad.as_bytes(),
+    };
+    let content = XChaCha20Poly1305::new(key.into())
+        .decrypt(XNonce::from_slice(nonce), payload)
+        .map_err(|_| "Wrong key or corrupted content".to_string())?;
+
+    String::from_utf8(content).map_err(|err| err.to_string())
+}
+
+/// Returns the length of the decrypted content without needing the key
+pub fn content_len(content: &str) -> usize {
+    match content.strip_prefix(PREFIX) {
+        Some(data) => base64::engine::general_purpose::STANDARD
+            .decode(data)
+            .map_or(0, |data| data.len().saturating_sub(24 + 16)),
+        None => content.len(),
+    }
+}
+
+/// Generates a new salt and derives a key from it, returns the vault to store and its key
+pub fn create(passphrase: &str) -> Result<(Vault, Key), String> {
+    let mut salt = [0; 16];
+    OsRng.fill_bytes(&mut salt);
+    let salt = base64::engine::general_purpose::STANDARD.encode(salt);
+
+    let key = derive_key(passphrase, &salt)?;
+    let vault = Vault {
+        id: 1,
+        salt,
+        verifier: encrypt(&key, VERIFIER_AAD, VERIFIER),
+    };
+
+    Ok((vault, key))
+}
+
+/// Derives the key of the vault, returns None if the passphrase is wrong
+pub fn open(vault: &Vault, passphrase: &str) -> Option<Key> {
+    derive_key(passphrase, &vault.salt)
+        .ok()
+        .filter(|key| verify(vault, key))
+}
+
+fn verify(vault: &Vault, key: &Key) -> bool {
+    decrypt(key, VERIFIER_AAD, &vault.verifier).is_ok_and(|verifier| verifier == VERIFIER)
+}
+
+pub fn encode_key(key: &Key) -> String {
+    base64::engine::general_purpose::STANDARD.encode(key)
+}
+
+pub fn decode_key(key: &str) -> Option<Key> {
+    base64::engine::general_purpose::STANDARD
+        .decode(key)
+        .ok()?
+        .try_into()
+        .ok()
+}
+
+/// Sends a one line request to the agent, returns None if the agent is not running
+pub fn agent_request(request: &str) -> Option<String> {
+    let mut stream = std::os::unix::net::UnixStream::connect(AGENT_SOCKET).ok()?;
+    stream
+        .set_read_timeout(Some(std::time::Duration::from_secs(5)))
+        .ok()?;
+    writeln!(stream, "{}", request).ok()?;
+
+    let mut response = String::new();
+    std::io::BufReader::new(stream)
+        .read_line(&mut response)
+        .ok()?;
+    Some(response.trim().to_string())
+}
+
+/// Fetches the key from the agent, ignoring it if it doesn't belong to the vault anymore
+fn agent_key(vault: &Vault) -> Option<Key> {
+    agent_request("GET")?
+        .strip_prefix("KEY ")
+        .and_then(decode_key)
+        .filter(|key| verify(vault, key))
+}
+
+/// Asks for a new master passphrase twice, returns None if they don't match
+pub fn new_passphrase() -> Option<String> {
+    let passphrase = match rpassword::prompt_password("New master passphrase: ") {
+        Ok(passphrase) => passphrase,
+        Err(err) => {
+            error!("Unable to read passphrase"; err);
+            return None;
+        }
+    };
+
+    if passphrase.is_empty() {
+        error!("Master passphrase cannot be empty");
+        return None;
+    }
+
+    match rpassword::prompt_password("Confirm master passphrase: ") {
+        Ok(confirmation) if confirmation == passphrase => Some(passphrase),
+        Ok(_) => {
+            error!("Master passphrases do not match");
+            None
+        }
+        Err(err) => {
+            error!("Unable to read passphrase"; err);
+            None
+        }
+    }
+}
+
+pub fn exists(connection: &mut SqliteConnection) -> bool {
+    vault::dsl::vault
+        .count()
+        .get_result::<i64>(connection)
+        .is_ok_and(|count| count > 0)
+}
+
+/// Stores the vault, replacing the previous one if there was one
+pub fn store(connection: &mut SqliteConnection, stored: &Vault) -> QueryResult<usize> {
+    diesel::replace_into(vault::dsl::vault)
+        .values(stored)
+        .execute(connection)
+}
+
+fn stored(connection: &mut SqliteConnection) -> Option<Vault> {
+    match vault::dsl::vault.first::<Vault>(connection) {
+        Ok(vault) => Some(vault),
+        Err(_) => {
+            error!("Secrets are not encrypted yet, run \"nova secrets init\" first");
+            None
+        }
+    }
+}
+
+fn prompt(vault: &Vault) -> Option<Key> {
+    let passphrase = match rpassword::prompt_password("Master passphrase: ") {
+        Ok(passphrase) => passphrase,
+        Err(err) => {
+            error!("Unable to read passphrase"; err);
+            return None;
+        }
+    };
+
+    let key = open(vault, &passphrase);
+    if key.is_none() {
+        error!("Wrong master passphrase");
+    }
+    key
+}
+
+/// Returns the key to decrypt secrets with, from the agent if it is unlocked or else by
+/// asking for the master passphrase
+pub fn unlock(connection: &mut SqliteConnection) -> Option<Key> {
+    let vault = stored(connection)?;
+    agent_key(&vault).or_else(|| prompt(&vault))
+}
+
+/// Returns the key only by asking for the master passphrase, even if the agent is unlocked,
+/// so that an unattended unlocked session can't be used to change the passphrase
+pub fn unlock_with_passphrase(connection: &mut SqliteConnection) -> Option<Key> {
+    prompt(&stored(connection)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn encrypt_round_trips() {
+        let key = [7; 32];
+        let aad = aad("demo", ".env");
+        let encrypted = encrypt(&key, &aad, "KEY=value\n");
+
+        assert!(is_encrypted(&encrypted));
+        assert_eq!(content_len(&encrypted), "KEY=value\n".len());
+        assert_eq!(
+            decrypt(&key, &aad, &encrypted),
+            Ok("KEY=value\n".to_string())
+        );
+    }
+
+    #[test]
+    fn decrypt_fails_with_wrong_key() {
+        let aad = aad("demo", ".env");
+        let encrypted = encrypt(&[7; 32], &aad, "KEY=value\n");
+
+        assert!(decrypt(&[8; 32], &aad, &encrypted).is_err());
+    }
+
+    #[test]
+    fn decrypt_fails_for_another_row() {
+        let key = [7; 32];
+        let encrypted = encrypt(&key, &aad("demo", ".env"), "KEY=value\n");
+
+        assert!(decrypt(&key, &aad("demo", ".env.local"), &encrypted).is_err());
+        assert!(decrypt(&key, &aad("other", ".env"), &encrypted).is_err());
+    }
+
+    #[test]
+    fn decrypt_fails_with_tampered_ciphertext() {
+        let key = [7; 32];
+        let aad = aad("demo", ".env");
+        let encrypted = encrypt(&key, &aad, "KEY=value\n");
+
+        let mut data = base64::engine::general_purpose::STANDARD
+            .decode(encrypted.strip_prefix(PREFIX).unwrap())
+            .unwrap();
+        *data.last_mut().unwrap() ^= 1;
+        let tampered = PREFIX.to_string() + &base64::engine::general_purpose::STANDARD.encode(data);
+
+        assert!(decrypt(&key, &aad, &tampered).is_err());
+    }
+}